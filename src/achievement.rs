@@ -0,0 +1,257 @@
+use crate::character::class::Category;
+use crate::event::{self, Event};
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Permanent goals that span across games. Unlike quests they don't grant
+/// rewards, and they are stored apart from the game data so they are kept
+/// even after a hard reset.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Achievement {
+    Flawless,
+    GiantSlayer,
+    RatBait,
+    Diplomat,
+}
+
+const BRIBES_FOR_DIPLOMAT: i32 = 50;
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::Flawless,
+        Achievement::GiantSlayer,
+        Achievement::RatBait,
+        Achievement::Diplomat,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::Flawless => "win a battle without taking damage",
+            Achievement::GiantSlayer => "defeat a legendary creature at level 1",
+            Achievement::RatBait => "die to a rat",
+            Achievement::Diplomat => "bribe 50 enemies",
+        }
+    }
+}
+
+/// The unlocked achievements with their unlock time, along with the
+/// counters needed to evaluate the pending ones.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Achievements {
+    unlocked: Vec<(Achievement, u64)>,
+    bribes: i32,
+
+    /// The enemy of the battle in progress.
+    #[serde(skip)]
+    enemy: Option<String>,
+    /// Whether the player was hurt during the battle in progress.
+    #[serde(skip)]
+    damaged: bool,
+}
+
+/// Unlocks achievements as the events happen.
+pub struct Handler;
+
+impl event::Subscriber for Handler {
+    fn handle(&mut self, game: &mut Game, event: &Event) {
+        handle(game, event);
+    }
+}
+
+fn handle(game: &mut Game, event: &Event) {
+    // take the achievements out so the game can be read while updating them
+    let mut achievements = std::mem::take(&mut game.achievements);
+    let unlocked = achievements.update(game, event);
+    game.achievements = achievements;
+
+    for achievement in unlocked {
+        Event::emit(game, Event::AchievementUnlocked { achievement });
+    }
+}
+
+impl Achievements {
+    /// Return every achievement along with its unlock date, if unlocked.
+    pub fn list(&self) -> Vec<(Achievement, Option<String>)> {
+        Achievement::ALL
+            .iter()
+            .map(|a| (*a, self.unlocked_at(*a).map(format_date)))
+            .collect()
+    }
+
+    fn unlocked_at(&self, achievement: Achievement) -> Option<u64> {
+        self.unlocked
+            .iter()
+            .find(|(a, _)| *a == achievement)
+            .map(|(_, at)| *at)
+    }
+
+    /// Update the achievements progress with the given event,
+    /// returning the ones that got unlocked by it.
+    fn update(&mut self, game: &Game, event: &Event) -> Vec<Achievement> {
+        let mut unlocked = Vec::new();
+
+        match event {
+            Event::EnemyAppears { enemy } => {
+                self.enemy = Some(enemy.name());
+                self.damaged = false;
+            }
+            Event::EnemyAttack { damage, .. }
+            | Event::StatusEffectDamage { damage }
+            | Event::ConfusedAttack { damage } => {
+                self.damaged |= *damage > 0;
+            }
+            Event::BattleWon {
+                enemy, levels_up, ..
+            } => {
+                if !self.damaged {
+                    unlocked.push(Achievement::Flawless);
+                }
+                let level_before = game.player.level - levels_up;
+                if enemy.category() == Some(Category::Legendary) && level_before == 1 {
+                    unlocked.push(Achievement::GiantSlayer);
+                }
+            }
+            Event::BattleLost if self.enemy.as_deref() == Some("rat") => {
+                unlocked.push(Achievement::RatBait);
+            }
+            Event::Bribe { cost } if *cost > 0 => {
+                self.bribes += 1;
+                if self.bribes >= BRIBES_FOR_DIPLOMAT {
+                    unlocked.push(Achievement::Diplomat);
+                }
+            }
+            _ => {}
+        }
+
+        unlocked.retain(|a| self.unlocked_at(*a).is_none());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        for achievement in &unlocked {
+            self.unlocked.push((*achievement, now));
+        }
+        unlocked
+    }
+}
+
+/// Format a unix timestamp as a YYYY-MM-DD date, in UTC.
+fn format_date(secs: u64) -> String {
+    // convert days since the epoch to a civil date
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!("1970-01-01", format_date(0));
+        assert_eq!("2000-02-29", format_date(951_782_400));
+        assert_eq!("2021-06-01", format_date(1_622_505_600 + 3600));
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut game = Game::new();
+        let enemy = Character::player();
+        let location = game.location.clone();
+        Event::emit(
+            &mut game,
+            Event::EnemyAppears {
+                enemy: enemy.clone(),
+            },
+        );
+        Event::emit(
+            &mut game,
+            Event::BattleWon {
+                enemy,
+                location,
+                xp: 10,
+                levels_up: 0,
+                gold: 10,
+                items: Vec::new(),
+            },
+        );
+        assert!(game
+            .achievements
+            .unlocked_at(Achievement::Flawless)
+            .is_some());
+
+        // achievements survive the hero's death
+        game.reset();
+        assert!(game
+            .achievements
+            .unlocked_at(Achievement::Flawless)
+            .is_some());
+    }
+
+    #[test]
+    fn test_update() {
+        let game = Game::new();
+        let mut achievements = Achievements::default();
+        let enemy = Character::player();
+
+        achievements.update(
+            &game,
+            &Event::EnemyAppears {
+                enemy: enemy.clone(),
+            },
+        );
+        achievements.update(&game, &Event::StatusEffectDamage { damage: 2 });
+        let won = Event::BattleWon {
+            enemy: enemy.clone(),
+            location: game.location.clone(),
+            xp: 10,
+            levels_up: 0,
+            gold: 10,
+            items: Vec::new(),
+        };
+        assert!(achievements.update(&game, &won).is_empty());
+
+        achievements.update(
+            &game,
+            &Event::EnemyAppears {
+                enemy: enemy.clone(),
+            },
+        );
+        assert_eq!(
+            vec![Achievement::Flawless],
+            achievements.update(&game, &won)
+        );
+        // achievements are unlocked only once
+        achievements.update(
+            &game,
+            &Event::EnemyAppears {
+                enemy: enemy.clone(),
+            },
+        );
+        assert!(achievements.update(&game, &won).is_empty());
+
+        for _ in 0..BRIBES_FOR_DIPLOMAT - 1 {
+            assert!(achievements
+                .update(&game, &Event::Bribe { cost: 10 })
+                .is_empty());
+        }
+        assert!(achievements
+            .update(&game, &Event::Bribe { cost: 0 })
+            .is_empty());
+        assert_eq!(
+            vec![Achievement::Diplomat],
+            achievements.update(&game, &Event::Bribe { cost: 10 })
+        );
+    }
+}
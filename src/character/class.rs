@@ -1,10 +1,17 @@
+use super::element::{Affinity, Element};
+use crate::datafile;
+use crate::item::loot::Loot;
 use crate::location;
+use crate::randomizer::with_rng;
+use once_cell::sync::Lazy;
 use rand::prelude::SliceRandom;
+use serde::Deserialize;
+use std::collections::HashSet;
 
 /// A stat represents an attribute of a character, such as strength or speed.
 /// This struct contains a stat starting value and the amount that should be
 /// applied when the level increases.
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Default)]
 pub struct Stat(pub i32, pub i32);
 
 impl Stat {
@@ -21,202 +28,304 @@ impl Stat {
     }
 }
 
+/// Enemy classes are grouped by how hard they are to find.
+/// The further from home, the more likely it is to find rare and legendary ones.
+/// Chest classes don't spawn when moving, they hide inside treasure chests.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum Category {
+    Common,
+    Rare,
+    Legendary,
+    Chest,
+}
+
 /// Classes are archetypes for characters.
 /// The struct contains a specific stat configuration such that all instances of
 /// the class have a similar combat behavior.
-#[derive(Debug)]
+/// Enemy classes are loaded from a bundled definitions file and can be
+/// extended or overridden by the user, see `load_enemies`.
+#[derive(Debug, Deserialize)]
 pub struct Class {
-    pub name: &'static str,
+    pub name: String,
+
+    /// The group the class belongs to. Classes without a category never
+    /// spawn randomly.
+    pub category: Option<Category>,
 
     pub hp: Stat,
+    /// Magic points, only relevant for classes that can use skills.
+    #[serde(default)]
+    pub mp: Stat,
     pub strength: Stat,
     pub speed: Stat,
 
     pub inflicts: Option<(super::StatusEffect, u32)>,
+
+    /// The element of the class attacks, when not overridden by a sword.
+    #[serde(default)]
+    pub element: Element,
+    /// Elements this class is weak against or resistant to. Any element
+    /// not listed is received with normal damage.
+    #[serde(default)]
+    pub affinities: Vec<(Element, Affinity)>,
+
+    /// Items that may be dropped when the class is defeated,
+    /// each with a 1 in N chance.
+    #[serde(default)]
+    pub drops: Vec<(Loot, u32)>,
+
+    /// Chance of spawning at near, mid and far distances from home,
+    /// relative to the rest of the classes. When omitted the
+    /// defaults for the class category are used.
+    pub weights: Option<(i32, i32, i32)>,
+
+    /// Locked classes don't spawn until a quest reward unlocks them.
+    #[serde(default)]
+    pub locked: bool,
 }
 
+static HERO: Lazy<Class> = Lazy::new(|| Class {
+    name: String::from("hero"),
+    category: None,
+    hp: Stat(30, 7),
+    mp: Stat(10, 3),
+    strength: Stat(12, 3),
+    speed: Stat(11, 2),
+    inflicts: None,
+    element: Element::Physical,
+    affinities: Vec::new(),
+    drops: Vec::new(),
+    weights: None,
+    locked: false,
+});
+
+static ENEMIES: Lazy<Vec<Class>> = Lazy::new(load_enemies);
+
 impl Class {
-    pub const HERO: Self = Self {
-        name: "hero",
-        hp: Stat(30, 7),
-        strength: Stat(12, 3),
-        speed: Stat(11, 2),
-        inflicts: None,
-    };
+    pub fn hero() -> &'static Self {
+        &HERO
+    }
 
-    pub fn random_enemy(distance: location::Distance) -> &'static Self {
-        weighted_choice(distance)
+    /// Choose an enemy randomly, if any class can be found at the distance.
+    /// Locked classes are only chosen if they are in the unlocked names.
+    pub fn random_enemy(
+        distance: location::Distance,
+        unlocked: &HashSet<String>,
+    ) -> Option<&'static Self> {
+        weighted_choice(&ENEMIES, distance, unlocked)
     }
-}
 
-pub const COMMON: &[Class] = &[RAT, WOLF, SNAKE, SLIME, SPIDER];
-pub const RARE: &[Class] = &[ZOMBIE, ORC, SKELETON, DEMON, VAMPIRE, DRAGON, GOLEM];
-pub const LEGENDARY: &[Class] = &[CHIMERA, BASILISK, MINOTAUR, BALROG, PHOENIX];
+    /// Choose one of the classes that hide in chests, if any.
+    pub fn random_mimic() -> Option<&'static Self> {
+        let mimics = Self::of_category(Category::Chest);
+        with_rng(|rng| mimics.as_slice().choose(rng).copied())
+    }
+
+    /// Find an enemy class by name.
+    pub fn enemy(name: &str) -> Option<&'static Self> {
+        ENEMIES.iter().find(|c| c.name == name)
+    }
+
+    /// The enemy classes of the given category that can be found without
+    /// unlocking them first.
+    pub fn of_category(category: Category) -> Vec<&'static Self> {
+        ENEMIES
+            .iter()
+            .filter(|c| c.category == Some(category) && c.spawns())
+            .collect()
+    }
+
+    /// Whether the class can be found before any unlock, either moving
+    /// around or hiding in chests.
+    fn spawns(&self) -> bool {
+        let distances = [
+            location::Distance::Near(1),
+            location::Distance::Mid(1),
+            location::Distance::Far(1),
+        ];
+        !self.locked
+            && (self.category == Some(Category::Chest)
+                || distances.iter().any(|d| self.weight(d) > 0))
+    }
+
+    /// How likely it is to find this class at the given distance,
+    /// before it's unlocked if it's a locked class.
+    fn weight(&self, distance: &location::Distance) -> i32 {
+        // the weights for each group of enemies are different depending on the distance
+        // the further from home, the bigger the chance to find difficult enemies
+        let (w_near, w_mid, w_far) = self.weights.unwrap_or(match self.category {
+            Some(Category::Common) => (9, 7, 1),
+            Some(Category::Rare) => (2, 10, 6),
+            Some(Category::Legendary) => (0, 1, 3),
+            Some(Category::Chest) | None => (0, 0, 0),
+        });
+
+        match distance {
+            location::Distance::Near(_) => w_near,
+            location::Distance::Mid(_) => w_mid,
+            location::Distance::Far(_) => w_far,
+        }
+    }
+}
 
 /// Choose an enemy randomly, with higher chance to difficult enemies the further from home.
-fn weighted_choice(distance: location::Distance) -> &'static Class {
-    // the weights for each group of enemies are different depending on the distance
-    // the further from home, the bigger the chance to find difficult enemies
-    let (w_near, w_mid, w_far) = match distance {
-        location::Distance::Near(_) => (9, 2, 0),
-        location::Distance::Mid(_) => (7, 10, 1),
-        location::Distance::Far(_) => (1, 6, 3),
-    };
-
-    // assign weights to each group
-    let near = COMMON.iter().map(|c| (c, w_near));
-    let mid = RARE.iter().map(|c| (c, w_mid));
-    let far = LEGENDARY.iter().map(|c| (c, w_far));
-
-    // make a weighted random choice
-    let mut rng = rand::thread_rng();
-    near.chain(mid)
-        .chain(far)
-        .collect::<Vec<(&Class, i32)>>()
-        .as_slice()
-        .choose_weighted(&mut rng, |(_c, weight)| *weight)
-        .unwrap()
-        .0
+fn weighted_choice<'a>(
+    enemies: &'a [Class],
+    distance: location::Distance,
+    unlocked: &HashSet<String>,
+) -> Option<&'a Class> {
+    with_rng(|rng| {
+        enemies
+            .choose_weighted(rng, |c| {
+                if c.locked && !unlocked.contains(&c.name) {
+                    0
+                } else {
+                    c.weight(&distance)
+                }
+            })
+            .ok()
+    })
 }
 
-// NOTE: we shouldn't end up in a place were the hero raises its value and as
-// a consequence the enemies raise it too, making them unbeatable.
-// Consider: 1. raising the enemy level solely (or primarily) based on distance;
-// 2. decreasing rates to prevent overgrowth at higher levels
-// as a starting measure, using increase rates way below those of the player
-
-const RAT: Class = Class {
-    name: "rat",
-    hp: Stat(10, 3),
-    strength: Stat(5, 2),
-    speed: Stat(16, 2),
-    inflicts: None,
-};
+const DEFAULT_ENEMIES: &str = include_str!("classes.json");
 
-const WOLF: Class = Class {
-    name: "wolf",
-    hp: Stat(15, 3),
-    strength: Stat(8, 2),
-    speed: Stat(12, 2),
-    inflicts: None,
-};
-
-const SNAKE: Class = Class {
-    name: "snake",
-    hp: Stat(13, 3),
-    strength: Stat(7, 2),
-    speed: Stat(6, 2),
-    inflicts: Some((super::StatusEffect::Poisoned, 5)),
-};
-
-const SLIME: Class = Class {
-    name: "slime",
-    hp: Stat(80, 3),
-    strength: Stat(3, 2),
-    speed: Stat(4, 2),
-    inflicts: Some((super::StatusEffect::Poisoned, 10)),
-};
-
-const SPIDER: Class = Class {
-    name: "spider",
-    hp: Stat(10, 3),
-    strength: Stat(9, 2),
-    speed: Stat(12, 2),
-    inflicts: Some((super::StatusEffect::Poisoned, 20)),
-};
-
-const ZOMBIE: Class = Class {
-    name: "zombie",
-    hp: Stat(50, 3),
-    strength: Stat(8, 2),
-    speed: Stat(6, 2),
-    inflicts: None,
-};
+/// Load the bundled enemy classes, then apply the ones defined by the user
+/// in the data directory: classes with a known name replace the bundled
+/// definition, the rest are added as new enemies. The user file is ignored
+/// if the resulting classes are not valid.
+fn load_enemies() -> Vec<Class> {
+    if let Some(data) = datafile::read_classes() {
+        let result = serde_json::from_slice::<Vec<Class>>(&data)
+            .map_err(|err| err.to_string())
+            .map(with_defaults)
+            .and_then(|enemies| validate(&enemies).map(|_| enemies));
+        match result {
+            Ok(enemies) => return enemies,
+            Err(err) => eprintln!("Ignoring invalid classes file: {}", err),
+        }
+    }
+    serde_json::from_str(DEFAULT_ENEMIES).unwrap()
+}
 
-const ORC: Class = Class {
-    name: "orc",
-    hp: Stat(35, 3),
-    strength: Stat(13, 2),
-    speed: Stat(12, 2),
-    inflicts: None,
-};
+/// Return the bundled classes with the custom ones applied over them.
+fn with_defaults(custom: Vec<Class>) -> Vec<Class> {
+    let mut enemies: Vec<Class> = serde_json::from_str(DEFAULT_ENEMIES).unwrap();
+    for class in custom {
+        enemies.retain(|c| c.name != class.name);
+        enemies.push(class);
+    }
+    enemies
+}
 
-const SKELETON: Class = Class {
-    name: "skeleton",
-    hp: Stat(30, 3),
-    strength: Stat(10, 2),
-    speed: Stat(10, 2),
-    inflicts: None,
-};
-
-const DEMON: Class = Class {
-    name: "demon",
-    hp: Stat(50, 3),
-    strength: Stat(10, 2),
-    speed: Stat(18, 2),
-    inflicts: Some((super::StatusEffect::Burning, 10)),
-};
-
-const VAMPIRE: Class = Class {
-    name: "vampire",
-    hp: Stat(50, 3),
-    strength: Stat(13, 2),
-    speed: Stat(10, 2),
-    inflicts: None,
-};
-
-const DRAGON: Class = Class {
-    name: "dragon",
-    hp: Stat(100, 3),
-    strength: Stat(25, 2),
-    speed: Stat(8, 2),
-    inflicts: Some((super::StatusEffect::Burning, 2)),
-};
-
-const GOLEM: Class = Class {
-    name: "golem",
-    hp: Stat(50, 3),
-    strength: Stat(45, 2),
-    speed: Stat(2, 1),
-    inflicts: None,
-};
-
-const CHIMERA: Class = Class {
-    name: "chimera",
-    hp: Stat(200, 2),
-    strength: Stat(90, 2),
-    speed: Stat(16, 2),
-    inflicts: Some((super::StatusEffect::Poisoned, 3)),
-};
-
-const BASILISK: Class = Class {
-    name: "basilisk",
-    hp: Stat(150, 3),
-    strength: Stat(100, 2),
-    speed: Stat(18, 2),
-    inflicts: Some((super::StatusEffect::Poisoned, 2)),
-};
-
-const MINOTAUR: Class = Class {
-    name: "minotaur",
-    hp: Stat(100, 3),
-    strength: Stat(60, 2),
-    speed: Stat(40, 2),
-    inflicts: None,
-};
-
-const BALROG: Class = Class {
-    name: "balrog",
-    hp: Stat(200, 3),
-    strength: Stat(200, 2),
-    speed: Stat(14, 2),
-    inflicts: Some((super::StatusEffect::Burning, 3)),
-};
-
-const PHOENIX: Class = Class {
-    name: "phoenix",
-    hp: Stat(350, 3),
-    strength: Stat(180, 2),
-    speed: Stat(28, 2),
-    inflicts: Some((super::StatusEffect::Burning, 2)),
-};
+/// Check that the random choices made with the classes can't fail:
+/// spawn weights can't be negative and some class must be found at every
+/// distance, while drop and status effect chances need to be at least 1 in 1.
+fn validate(enemies: &[Class]) -> Result<(), String> {
+    for class in enemies {
+        if let Some((near, mid, far)) = class.weights {
+            if near < 0 || mid < 0 || far < 0 || near + mid + far == 0 {
+                return Err(format!("{}: invalid weights", class.name));
+            }
+        }
+        if class.drops.iter().any(|(_, ratio)| *ratio < 1) {
+            return Err(format!("{}: drop chance must be at least 1", class.name));
+        }
+        if let Some((_, 0)) = class.inflicts {
+            return Err(format!("{}: inflict chance must be at least 1", class.name));
+        }
+    }
+
+    // quests and chests need enemies of every category
+    for category in &[
+        Category::Common,
+        Category::Rare,
+        Category::Legendary,
+        Category::Chest,
+    ] {
+        if !enemies
+            .iter()
+            .any(|c| c.category == Some(*category) && c.spawns())
+        {
+            return Err(format!("no {:?} enemies", category).to_lowercase());
+        }
+    }
+
+    for (name, distance) in &[
+        ("near", location::Distance::Near(1)),
+        ("mid", location::Distance::Mid(1)),
+        ("far", location::Distance::Far(1)),
+    ] {
+        let weights = enemies
+            .iter()
+            .filter(|c| !c.locked)
+            .map(|c| c.weight(distance));
+        if weights.sum::<i32>() == 0 {
+            return Err(format!("no enemies found at {} distance", name));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_enemies() {
+        let enemies: Vec<Class> = serde_json::from_str(DEFAULT_ENEMIES).unwrap();
+        assert!(!enemies.is_empty());
+
+        for category in &[Category::Common, Category::Rare, Category::Legendary] {
+            assert!(enemies.iter().any(|c| c.category == Some(*category)));
+        }
+        assert!(Class::random_mimic().is_some());
+
+        let slime = enemies.iter().find(|c| c.name == "slime").unwrap();
+        assert_eq!(80, slime.hp.base());
+        assert_eq!(Element::Poison, slime.element);
+        assert_eq!(2, slime.affinities.len());
+        assert_eq!(0, slime.mp.base());
+        assert!(validate(&enemies).is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let parse = |json: &str| serde_json::from_str::<Vec<Class>>(json).unwrap();
+        let class = |extra: &str| {
+            format!(
+                r#"[{{"name": "blob", "category": "Common", "hp": [10, 1], "strength": [1, 1], "speed": [1, 1]{}}}]"#,
+                extra
+            )
+        };
+
+        let check = |extra: &str| validate(&with_defaults(parse(&class(extra))));
+
+        assert!(check("").is_ok());
+        assert!(check(r#", "weights": [1, 0, 0]"#).is_ok());
+        assert!(check(r#", "locked": true"#).is_ok());
+        assert!(check(r#", "weights": [-1, 5, 5]"#).is_err());
+        assert!(check(r#", "weights": [0, 0, 0]"#).is_err());
+        assert!(check(r#", "drops": [["Potion", 0]]"#).is_err());
+        assert!(check(r#", "inflicts": ["Poisoned", 0]"#).is_err());
+        // the only chest class can't be removed nor locked
+        let mimic = r#"[{"name": "mimic", "category": "Chest", "hp": [10, 1], "strength": [1, 1], "speed": [1, 1], "locked": true}]"#;
+        assert!(validate(&with_defaults(parse(mimic))).is_err());
+        let mimic = mimic
+            .replace("\"Chest\"", "\"Common\"")
+            .replace(", \"locked\": true", "");
+        assert!(validate(&with_defaults(parse(&mimic))).is_err());
+        // a single class lacks the rest of the categories
+        assert!(validate(&parse(&class(""))).is_err());
+
+        assert!(parse(&class(""))[0].spawns());
+        assert!(!parse(&class(r#", "locked": true"#))[0].spawns());
+        assert!(!parse(&class(r#", "weights": [0, 0, 0]"#))[0].spawns());
+
+        let mut unlocked = HashSet::new();
+        let locked = parse(&class(r#", "locked": true"#));
+        assert!(weighted_choice(&locked, location::Distance::Near(1), &unlocked).is_none());
+        unlocked.insert(String::from("blob"));
+        assert!(weighted_choice(&locked, location::Distance::Near(1), &unlocked).is_some());
+        assert!(weighted_choice(&[], location::Distance::Near(1), &unlocked).is_none());
+    }
+}
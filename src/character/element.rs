@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+use std::fmt;
+
+/// The kind of damage dealt by an attack.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Element {
+    #[default]
+    Physical,
+    Fire,
+    Poison,
+    Holy,
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Element::Physical => "physical",
+            Element::Fire => "fire",
+            Element::Poison => "poison",
+            Element::Holy => "holy",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How a character reacts to damage of a given element.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Affinity {
+    Weak,
+    Normal,
+    Resist,
+    Immune,
+}
+
+impl Affinity {
+    /// Scale the damage according to the affinity.
+    pub fn apply(&self, damage: i32) -> i32 {
+        match self {
+            Affinity::Weak => damage * 3 / 2,
+            Affinity::Normal => damage,
+            Affinity::Resist => max(1, damage / 2),
+            Affinity::Immune => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(15, Affinity::Weak.apply(10));
+        assert_eq!(10, Affinity::Normal.apply(10));
+        assert_eq!(5, Affinity::Resist.apply(10));
+        assert_eq!(1, Affinity::Resist.apply(1));
+        assert_eq!(0, Affinity::Immune.apply(10));
+    }
+}
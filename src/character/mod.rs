@@ -1,14 +1,21 @@
 use crate::item::equipment;
 use crate::item::equipment::Equipment;
+use crate::item::loot::Loot;
 use crate::location;
 use crate::randomizer::{random, Randomizer};
 use class::Class;
+use element::{Affinity, Element};
 use serde::{Deserialize, Serialize};
+use skill::Skill;
 use std::cmp::{max, min};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 pub mod class;
+pub mod element;
+pub mod skill;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct Character {
     #[serde(skip, default = "default_class")]
@@ -22,15 +29,62 @@ pub struct Character {
     pub max_hp: i32,
     pub current_hp: i32,
 
+    pub max_mp: i32,
+    pub current_mp: i32,
+    pub skills: Vec<Skill>,
+
     pub strength: i32,
     pub speed: i32,
     pub status_effect: Option<StatusEffect>,
+    /// How many turns or moves until the current status effect wears off.
+    pub status_turns: i32,
+    /// Active buffs and the turns or moves left for each of them.
+    pub buffs: HashMap<Buff, i32>,
 }
 
+/// Negative effects inflicted by enemy attacks.
+/// Only one can be active at a time and they expire after a number of turns,
+/// or when the hero goes home or uses a remedy.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum StatusEffect {
     Burning,
     Poisoned,
+    Stunned,
+    Slowed,
+    Confused,
+}
+
+impl StatusEffect {
+    /// How many turns or moves the effect lasts once inflicted.
+    pub fn duration(&self) -> i32 {
+        match self {
+            StatusEffect::Burning => 10,
+            StatusEffect::Poisoned => 15,
+            StatusEffect::Stunned => 1,
+            StatusEffect::Slowed => 5,
+            StatusEffect::Confused => 3,
+        }
+    }
+}
+
+/// Positive effects granted by items. Unlike status effects, several
+/// different buffs can be active at the same time.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Buff {
+    Regen,
+    Haste,
+    Might,
+}
+
+impl fmt::Display for Buff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Buff::Regen => "regen",
+            Buff::Haste => "haste",
+            Buff::Might => "might",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 pub struct Dead;
@@ -43,22 +97,47 @@ impl Default for Character {
 
 // Always attach the static hero class to deserialized characters
 fn default_class() -> &'static Class {
-    &Class::HERO
+    Class::hero()
 }
 
 impl Character {
     pub fn player() -> Self {
-        Self::new(&Class::HERO, 1)
+        Self::new(Class::hero(), 1)
     }
 
-    pub fn enemy(level: i32, distance: location::Distance) -> Self {
-        Self::new(Class::random_enemy(distance), level)
+    /// Build a random enemy for the given distance, if any class can be found
+    /// there. Locked classes only appear if they are in the unlocked names.
+    pub fn enemy(
+        level: i32,
+        distance: location::Distance,
+        unlocked: &HashSet<String>,
+    ) -> Option<Self> {
+        Class::random_enemy(distance, unlocked).map(|class| Self::new(class, level))
+    }
+
+    /// Build an enemy of one of the classes that hide in chests, if there's any.
+    pub fn mimic(level: i32) -> Option<Self> {
+        Class::random_mimic().map(|class| Self::new(class, level))
+    }
+
+    /// Build an enemy of the class with the given name, if there's one.
+    pub fn enemy_of_class(name: &str, level: i32) -> Option<Self> {
+        Class::enemy(name).map(|class| Self::new(class, level))
     }
 
     pub fn name(&self) -> String {
         self.class.name.to_string()
     }
 
+    pub fn category(&self) -> Option<class::Category> {
+        self.class.category
+    }
+
+    /// The items this character may drop when defeated, with their 1 in N chance.
+    pub fn drops(&self) -> &'static [(Loot, u32)] {
+        &self.class.drops
+    }
+
     pub fn is_player(&self) -> bool {
         // kind of ugly but does the job
         self.class.name == "hero"
@@ -73,9 +152,14 @@ impl Character {
             xp: 0,
             max_hp: class.hp.base(),
             current_hp: class.hp.base(),
+            max_mp: class.mp.base(),
+            current_mp: class.mp.base(),
+            skills: Vec::new(),
             strength: class.strength.base(),
             speed: class.speed.base(),
             status_effect: None,
+            status_turns: 0,
+            buffs: HashMap::new(),
         };
 
         for _ in 1..level {
@@ -97,6 +181,12 @@ impl Character {
         let previous_damage = self.max_hp - self.current_hp;
         self.max_hp += random().stat_increase(self.class.hp.increase());
         self.current_hp = self.max_hp - previous_damage;
+
+        if self.class.mp.increase() > 0 {
+            let previous_spent = self.max_mp - self.current_mp;
+            self.max_mp += random().stat_increase(self.class.mp.increase());
+            self.current_mp = self.max_mp - previous_spent;
+        }
     }
 
     /// Add to the accumulated experience points, possibly increasing the level.
@@ -141,6 +231,28 @@ impl Character {
         self.heal(self.max_hp)
     }
 
+    /// Restore all magic points to the max_mp.
+    pub fn restore_mp(&mut self) {
+        self.current_mp = self.max_mp;
+    }
+
+    /// Add the skill to the learned ones, if the character level allows it.
+    pub fn learn(&mut self, skill: Skill) -> Result<(), skill::Error> {
+        if self.skills.contains(&skill) {
+            Err(skill::Error::AlreadyLearned)
+        } else if self.level < skill.unlock_level() {
+            Err(skill::Error::Locked)
+        } else {
+            self.skills.push(skill);
+            Ok(())
+        }
+    }
+
+    /// Whether the skill was learned and there are enough magic points to use it.
+    pub fn can_use(&self, skill: Skill) -> bool {
+        self.skills.contains(&skill) && self.current_mp >= skill.mp_cost()
+    }
+
     /// How many experience points are required to move to the next level.
     pub fn xp_for_next(&self) -> i32 {
         let exp = 1.5;
@@ -156,7 +268,51 @@ impl Character {
 
     pub fn attack(&self) -> i32 {
         let sword_str = self.sword.as_ref().map_or(0, |s| s.strength());
-        self.strength + sword_str
+        let might = if self.has_buff(Buff::Might) {
+            self.strength / 2
+        } else {
+            0
+        };
+        self.strength + sword_str + might
+    }
+
+    /// The element of this character's attacks, given by the sword
+    /// if it has one or by the class otherwise.
+    pub fn attack_element(&self) -> Element {
+        self.sword
+            .as_ref()
+            .and_then(|s| s.element())
+            .unwrap_or(self.class.element)
+    }
+
+    /// How this character reacts to damage of the given element.
+    /// A shield that resists the element prevents weakness against it.
+    pub fn affinity(&self, element: Element) -> Affinity {
+        let affinity = self
+            .class
+            .affinities
+            .iter()
+            .find(|(e, _)| *e == element)
+            .map_or(Affinity::Normal, |(_, a)| *a);
+
+        let shield_resists = self.shield.as_ref().and_then(|s| s.resists()) == Some(element);
+        match affinity {
+            Affinity::Weak | Affinity::Normal if shield_resists => Affinity::Resist,
+            _ => affinity,
+        }
+    }
+
+    /// The speed used to decide turns in battle, after applying
+    /// haste and slowness.
+    pub fn effective_speed(&self) -> i32 {
+        let mut speed = self.speed;
+        if self.has_buff(Buff::Haste) {
+            speed += speed / 2;
+        }
+        if self.status_effect == Some(StatusEffect::Slowed) {
+            speed /= 2;
+        }
+        max(1, speed)
     }
 
     pub fn deffense(&self) -> i32 {
@@ -182,9 +338,56 @@ impl Character {
         self.class.inflicts
     }
 
+    /// Set the given status effect, replacing the current one if any.
+    pub fn inflict(&mut self, status: StatusEffect) {
+        self.status_effect = Some(status);
+        self.status_turns = status.duration();
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.status_effect == Some(StatusEffect::Stunned)
+    }
+
+    pub fn is_confused(&self) -> bool {
+        self.status_effect == Some(StatusEffect::Confused)
+    }
+
+    pub fn add_buff(&mut self, buff: Buff, turns: i32) {
+        self.buffs.insert(buff, turns);
+    }
+
+    pub fn has_buff(&self, buff: Buff) -> bool {
+        self.buffs.contains_key(&buff)
+    }
+
+    /// Count down one turn for the status effect and buffs, removing the ones
+    /// that expired. Return the health points restored by regeneration, if any.
+    pub fn tick_effects(&mut self) -> Option<i32> {
+        let regen = if self.has_buff(Buff::Regen) {
+            Some(self.heal(max(1, self.max_hp / 20)))
+        } else {
+            None
+        };
+
+        if self.status_effect.is_some() {
+            self.status_turns -= 1;
+            if self.status_turns <= 0 {
+                self.status_effect = None;
+            }
+        }
+
+        for turns in self.buffs.values_mut() {
+            *turns -= 1;
+        }
+        self.buffs.retain(|_, turns| *turns > 0);
+
+        regen
+    }
+
     pub fn maybe_remove_status_effect(&mut self) -> bool {
         if self.status_effect.is_some() {
             self.status_effect = None;
+            self.status_turns = 0;
             return true;
         }
         false
@@ -209,14 +412,25 @@ impl Character {
 mod tests {
     use super::*;
     use class::Stat;
+    use once_cell::sync::Lazy;
 
-    const TEST_CLASS: Class = Class {
-        name: "test",
+    static TEST_CLASS: Lazy<Class> = Lazy::new(|| Class {
+        name: String::from("test"),
+        category: None,
         hp: Stat(25, 7),
+        mp: Stat(10, 3),
         strength: Stat(10, 3),
         speed: Stat(10, 2),
         inflicts: None,
-    };
+        element: Element::Physical,
+        affinities: vec![
+            (Element::Fire, Affinity::Weak),
+            (Element::Holy, Affinity::Immune),
+        ],
+        drops: Vec::new(),
+        weights: None,
+        locked: false,
+    });
 
     fn new_char() -> Character {
         Character::new(&TEST_CLASS, 1)
@@ -437,4 +651,82 @@ mod tests {
         assert!(hero.receive_status_effect_damage().is_err());
         assert!(hero.is_dead());
     }
+
+    #[test]
+    fn test_learn() {
+        let mut hero = new_char();
+        assert!(!hero.can_use(Skill::PowerStrike));
+        assert!(hero.learn(Skill::PowerStrike).is_err());
+
+        hero.level = Skill::PowerStrike.unlock_level();
+        assert!(hero.learn(Skill::PowerStrike).is_ok());
+        assert!(hero.learn(Skill::PowerStrike).is_err());
+        assert!(hero.can_use(Skill::PowerStrike));
+
+        hero.current_mp = Skill::PowerStrike.mp_cost() - 1;
+        assert!(!hero.can_use(Skill::PowerStrike));
+        hero.restore_mp();
+        assert_eq!(hero.max_mp, hero.current_mp);
+    }
+
+    #[test]
+    fn test_affinity() {
+        let mut hero = new_char();
+        assert_eq!(Element::Physical, hero.attack_element());
+        assert_eq!(Affinity::Normal, hero.affinity(Element::Physical));
+        assert_eq!(Affinity::Weak, hero.affinity(Element::Fire));
+        assert_eq!(Affinity::Immune, hero.affinity(Element::Holy));
+
+        hero.sword = Some(equipment::Sword::with_element(1, Element::Holy));
+        assert_eq!(Element::Holy, hero.attack_element());
+
+        hero.shield = Some(equipment::Shield::with_resistance(1, Element::Fire));
+        assert_eq!(Affinity::Resist, hero.affinity(Element::Fire));
+        hero.shield = Some(equipment::Shield::with_resistance(1, Element::Holy));
+        assert_eq!(Affinity::Immune, hero.affinity(Element::Holy));
+    }
+
+    #[test]
+    fn test_tick_effects() {
+        let mut hero = new_char();
+
+        hero.inflict(StatusEffect::Slowed);
+        assert_eq!(5, hero.effective_speed());
+        for _ in 0..StatusEffect::Slowed.duration() - 1 {
+            hero.tick_effects();
+        }
+        assert_eq!(Some(StatusEffect::Slowed), hero.status_effect);
+        hero.tick_effects();
+        assert!(hero.status_effect.is_none());
+        assert_eq!(10, hero.effective_speed());
+
+        hero.add_buff(Buff::Haste, 2);
+        hero.add_buff(Buff::Regen, 1);
+        hero.add_buff(Buff::Might, 2);
+        assert_eq!(15, hero.effective_speed());
+        assert_eq!(15, hero.attack());
+
+        hero.current_hp = 10;
+        assert_eq!(Some(1), hero.tick_effects());
+        assert_eq!(11, hero.current_hp);
+        assert!(!hero.has_buff(Buff::Regen));
+        assert!(hero.has_buff(Buff::Haste));
+
+        assert_eq!(None, hero.tick_effects());
+        assert!(hero.buffs.is_empty());
+        assert_eq!(10, hero.attack());
+
+        // a removed effect doesn't shorten the next one
+        hero.inflict(StatusEffect::Confused);
+        hero.tick_effects();
+        assert!(hero.maybe_remove_status_effect());
+        assert_eq!(0, hero.status_turns);
+        hero.inflict(StatusEffect::Stunned);
+        for _ in 0..StatusEffect::Stunned.duration() - 1 {
+            hero.tick_effects();
+        }
+        assert!(hero.is_stunned());
+        hero.tick_effects();
+        assert!(hero.status_effect.is_none());
+    }
 }
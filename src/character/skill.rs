@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Special battle actions that consume magic points.
+/// Skills are unlocked as the hero levels up and need to be learned before use.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Skill {
+    PowerStrike,
+    Heal,
+    FireBolt,
+}
+
+pub enum Error {
+    Locked,
+    AlreadyLearned,
+}
+
+impl Skill {
+    pub const ALL: &'static [Skill] = &[Skill::PowerStrike, Skill::Heal, Skill::FireBolt];
+
+    /// Find a skill by the name used in the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|s| s.name() == name).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::PowerStrike => "power-strike",
+            Skill::Heal => "heal",
+            Skill::FireBolt => "fire-bolt",
+        }
+    }
+
+    /// The hero level at which the skill can be learned.
+    pub fn unlock_level(&self) -> i32 {
+        match self {
+            Skill::PowerStrike => 3,
+            Skill::Heal => 5,
+            Skill::FireBolt => 8,
+        }
+    }
+
+    pub fn mp_cost(&self) -> i32 {
+        match self {
+            Skill::PowerStrike => 4,
+            Skill::Heal => 6,
+            Skill::FireBolt => 8,
+        }
+    }
+}
+
+impl fmt::Display for Skill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Determines when the hero uses skills during the automatic battles.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum SkillPolicy {
+    /// Only plain attacks.
+    Never,
+    /// Only the heal skill when health is low.
+    Defensive,
+    /// Heal when health is low, attack skills against stronger enemies.
+    #[default]
+    Balanced,
+    /// Heal when health is low, attack skills whenever there's enough MP.
+    Aggressive,
+}
+
+impl SkillPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "never" => Some(SkillPolicy::Never),
+            "defensive" => Some(SkillPolicy::Defensive),
+            "balanced" => Some(SkillPolicy::Balanced),
+            "aggressive" => Some(SkillPolicy::Aggressive),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SkillPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SkillPolicy::Never => "never",
+            SkillPolicy::Defensive => "defensive",
+            SkillPolicy::Balanced => "balanced",
+            SkillPolicy::Aggressive => "aggressive",
+        };
+        write!(f, "{}", name)
+    }
+}
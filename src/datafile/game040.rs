@@ -71,7 +71,7 @@ mod tests {
         assert!(new_game.player.shield.is_some());
         assert_eq!(1 as usize, *new_game.inventory().get("potion").unwrap());
         // pick up tombstone @ home
-        new_game.inspect();
+        assert!(new_game.inspect().is_ok());
         assert_eq!(2 as usize, *new_game.inventory().get("potion").unwrap());
     }
 }
@@ -1,44 +1,173 @@
+use crate::achievement::Achievements;
+use crate::error::Error;
 use crate::game;
+use crate::location::Location;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{fs, io, path};
 
 mod game040;
 
-pub struct NotFound;
+const DATA_FILE: &str = "data";
 
-pub fn load() -> Result<game::Game, NotFound> {
-    let data: Vec<u8> = read()?;
-    let game: game::Game = if let Ok(game) = serde_json::from_slice(&data) {
-        game
-    } else {
-        // if json deserialization fails, attempt bincode assuming
-        // it may be a file from v0.4.0
-        game040::deserialize(&data).unwrap()
+/// Whether the files written by the user are ignored. Only the binary reads
+/// them, so the library can be embedded without touching ~/.rpg.
+static BUNDLED_ONLY: AtomicBool = AtomicBool::new(!cfg!(feature = "cli"));
+
+/// Load the saved game, if any. Data files from v0.4.0 are converted
+/// to the current format.
+pub fn load() -> Result<Option<game::Game>, Error> {
+    let data = match read() {
+        Some(data) => data,
+        None => return Ok(None),
     };
-    Ok(game)
+    if let Ok(game) = serde_json::from_slice(&data) {
+        return Ok(Some(game));
+    }
+    // if json deserialization fails, attempt bincode assuming
+    // it may be a file from v0.4.0
+    game040::deserialize(&data)
+        .map(Some)
+        .map_err(|_| Error::CorruptSave)
 }
 
-pub fn save(game: &game::Game) -> Result<(), io::Error> {
-    let data = serde_json::to_vec(game).unwrap();
-    write(data)
+/// Load the achievements, which are kept apart from the game data.
+pub fn load_achievements() -> Achievements {
+    fs::read(achievements_file())
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
 }
 
+/// The serialized state of a game at some point, used to tell whether
+/// it changed since then and needs to be saved.
+pub struct Snapshot {
+    game: Vec<u8>,
+    achievements: Vec<u8>,
+}
+
+impl Snapshot {
+    pub fn of(game: &game::Game) -> Self {
+        Self {
+            game: serde_json::to_vec(game).unwrap(),
+            achievements: serde_json::to_vec(&game.achievements).unwrap(),
+        }
+    }
+}
+
+/// Save the game and its achievements only if they changed since the given
+/// snapshot was taken, so read-only commands don't rewrite the data files.
+pub fn save(game: &game::Game, snapshot: &Snapshot) -> Result<(), io::Error> {
+    let current = Snapshot::of(game);
+    if current.game != snapshot.game {
+        write(&file(), current.game)?;
+    }
+    if current.achievements != snapshot.achievements {
+        write(&achievements_file(), current.achievements)?;
+    }
+    Ok(())
+}
+
+/// Remove the saved game. Achievements are meant to survive hard resets,
+/// and the files written by the user, such as the enemy classes, quest
+/// packs, hooks and home, are kept as well.
 pub fn remove() {
-    let rpg_dir = rpg_dir();
-    if rpg_dir.exists() {
-        fs::remove_dir_all(&rpg_dir).unwrap();
+    remove_game(&rpg_dir());
+}
+
+fn remove_game(rpg_dir: &path::Path) {
+    let file = rpg_dir.join(DATA_FILE);
+    if file.exists() {
+        fs::remove_file(file).unwrap();
+    }
+}
+
+/// Ignore the files written by the user from now on, such as the enemy
+/// classes, quest packs, hooks and home, so only the bundled data is used.
+pub fn use_bundled_data() {
+    BUNDLED_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Read the home directory set by the user, if any.
+pub fn read_home() -> Option<path::PathBuf> {
+    let home = fs::read_to_string(user_file("home")?).ok()?;
+    Some(path::PathBuf::from(home.trim()))
+}
+
+/// Store the home directory, or go back to the user's home if none is given.
+pub fn write_home(home: Option<&Location>) -> Result<(), io::Error> {
+    match home {
+        Some(home) => {
+            create_dir()?;
+            fs::write(home_file(), home.path_string())
+        }
+        None if home_file().exists() => fs::remove_file(home_file()),
+        None => Ok(()),
+    }
+}
+
+/// Read the user defined enemy classes, if the file exists.
+pub fn read_classes() -> Option<Vec<u8>> {
+    fs::read(user_file("classes.json")?).ok()
+}
+
+/// Read the user quest pack files, along with their names.
+pub fn read_quest_packs() -> Vec<(String, Vec<u8>)> {
+    let mut packs = Vec::new();
+    if let Some(Ok(entries)) = user_file("quests").map(fs::read_dir) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.extension() == Some("json".as_ref()) {
+                if let Ok(data) = fs::read(&path) {
+                    let name = path.file_name().unwrap().to_string_lossy().to_string();
+                    packs.push((name, data));
+                }
+            }
+        }
+    }
+    packs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    packs
+}
+
+/// The user hook scripts, indexed by the name of the event that triggers
+/// them: both `level_up` and `level_up.sh` are run on level ups, sorted
+/// by file name.
+pub fn read_hooks() -> HashMap<String, Vec<path::PathBuf>> {
+    user_file("hooks")
+        .map(|dir| hooks_in(&dir))
+        .unwrap_or_default()
+}
+
+fn hooks_in(dir: &path::Path) -> HashMap<String, Vec<path::PathBuf>> {
+    let mut hooks: HashMap<String, Vec<path::PathBuf>> = HashMap::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if let (true, Some(stem)) = (path.is_file(), path.file_stem()) {
+                let name = stem.to_string_lossy().to_string();
+                hooks.entry(name).or_default().push(path.clone());
+            }
+        }
+    }
+    for scripts in hooks.values_mut() {
+        scripts.sort();
     }
+    hooks
 }
 
-fn read() -> Result<Vec<u8>, NotFound> {
-    fs::read(file()).map_err(|_| NotFound)
+fn read() -> Option<Vec<u8>> {
+    fs::read(file()).ok()
 }
 
-fn write(data: Vec<u8>) -> Result<(), io::Error> {
+fn write(file: &path::Path, data: Vec<u8>) -> Result<(), io::Error> {
+    create_dir()?;
+    fs::write(file, &data)
+}
+
+fn create_dir() -> Result<(), io::Error> {
     let rpg_dir = rpg_dir();
     if !rpg_dir.exists() {
-        fs::create_dir(&rpg_dir).unwrap();
+        fs::create_dir(&rpg_dir)?;
     }
-    fs::write(file(), &data)
+    Ok(())
 }
 
 fn rpg_dir() -> path::PathBuf {
@@ -46,5 +175,70 @@ fn rpg_dir() -> path::PathBuf {
 }
 
 fn file() -> path::PathBuf {
-    rpg_dir().join("data")
+    rpg_dir().join(DATA_FILE)
+}
+
+fn home_file() -> path::PathBuf {
+    rpg_dir().join("home")
+}
+
+fn achievements_file() -> path::PathBuf {
+    rpg_dir().join("achievements")
+}
+
+/// The path of a file written by the user, unless they are ignored.
+fn user_file(name: &str) -> Option<path::PathBuf> {
+    if BUNDLED_ONLY.load(Ordering::Relaxed) {
+        None
+    } else {
+        Some(rpg_dir().join(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_keeps_user_files() {
+        let dir = std::env::temp_dir().join(format!("rpg-datafile-{}", std::process::id()));
+        let user_files = ["achievements", "classes.json", "home", "hooks/level_up.sh"];
+        fs::create_dir_all(dir.join("hooks")).unwrap();
+        fs::create_dir_all(dir.join("quests")).unwrap();
+        fs::write(dir.join("quests/extra.json"), "[]").unwrap();
+        fs::write(dir.join(DATA_FILE), "{}").unwrap();
+        for file in &user_files {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        remove_game(&dir);
+        assert!(!dir.join(DATA_FILE).exists());
+        assert!(dir.join("quests/extra.json").exists());
+        for file in &user_files {
+            assert!(dir.join(file).exists());
+        }
+
+        // removing again is a no-op
+        remove_game(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_hooks_in() {
+        let dir = std::env::temp_dir().join(format!("rpg-hooks-{}", std::process::id()));
+        fs::create_dir_all(dir.join("chest_found")).unwrap();
+        for file in &["level_up.sh", "level_up", "battle_won.py"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        let hooks = hooks_in(&dir);
+        assert_eq!(
+            vec![dir.join("level_up"), dir.join("level_up.sh")],
+            hooks["level_up"]
+        );
+        assert_eq!(vec![dir.join("battle_won.py")], hooks["battle_won"]);
+        // directories are not scripts
+        assert!(!hooks.contains_key("chest_found"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,122 @@
+use crate::character;
+use crate::game;
+use crate::item::shop;
+use std::{fmt, io};
+
+/// The errors that can end a command. Each kind has its own exit code,
+/// so shell wrappers can branch on them:
+///
+/// * 1: the hero died and the game was reset.
+/// * 3: the destination path doesn't exist.
+/// * 4: not enough gold to buy an item.
+/// * 5: the item is not in the inventory or not sold at the shop.
+/// * 6: the game data file can't be read.
+/// * 7: invalid command input, e.g. an unknown skill or shop away from home.
+/// * 8: the terminal or a user file can't be written.
+///
+/// Exit code 2 is left for the command line parsing errors.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    HeroDied,
+    NoSuchPath,
+    NotEnoughGold,
+    ItemNotFound,
+    ItemNotAvailable,
+    CorruptSave,
+    InvalidInput(String),
+    Io(String),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::HeroDied => 1,
+            Error::NoSuchPath => 3,
+            Error::NotEnoughGold => 4,
+            Error::ItemNotFound | Error::ItemNotAvailable => 5,
+            Error::CorruptSave => 6,
+            Error::InvalidInput(_) => 7,
+            Error::Io(_) => 8,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::HeroDied => write!(f, "The hero died."),
+            Error::NoSuchPath => write!(f, "No such file or directory"),
+            Error::NotEnoughGold => write!(f, "Not enough gold."),
+            Error::ItemNotFound => write!(f, "Item not found."),
+            Error::ItemNotAvailable => write!(f, "Item not available."),
+            Error::CorruptSave => write!(
+                f,
+                "Could not read the game data, run `rpg reset --hard` to start over."
+            ),
+            Error::InvalidInput(message) => write!(f, "{}", message),
+            Error::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<character::Dead> for Error {
+    fn from(_: character::Dead) -> Self {
+        Error::HeroDied
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+impl From<game::ItemNotFound> for Error {
+    fn from(_: game::ItemNotFound) -> Self {
+        Error::ItemNotFound
+    }
+}
+
+impl From<shop::Error> for Error {
+    fn from(err: shop::Error) -> Self {
+        match err {
+            shop::Error::NotEnoughGold => Error::NotEnoughGold,
+            shop::Error::ItemNotAvailable => Error::ItemNotAvailable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exit_codes() {
+        let errors = [
+            Error::HeroDied,
+            Error::NoSuchPath,
+            Error::NotEnoughGold,
+            Error::ItemNotFound,
+            Error::ItemNotAvailable,
+            Error::CorruptSave,
+            Error::InvalidInput(String::from("Skill not found.")),
+            Error::Io(String::from("Broken pipe")),
+        ];
+        let codes: HashSet<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        // missing and unavailable items share their exit code
+        assert_eq!(errors.len() - 1, codes.len());
+        assert_eq!(
+            Error::ItemNotFound.exit_code(),
+            Error::ItemNotAvailable.exit_code()
+        );
+        assert!(!codes.contains(&0));
+        assert!(!codes.contains(&2));
+
+        assert_eq!(
+            Error::NotEnoughGold,
+            Error::from(shop::Error::NotEnoughGold)
+        );
+        assert_eq!("Skill not found.", errors[6].to_string());
+    }
+}
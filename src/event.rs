@@ -1,20 +1,31 @@
+use crate::achievement::{self, Achievement};
+use crate::character::element::Affinity;
+use crate::character::skill::Skill;
 use crate::character::Character;
 use crate::game;
 use crate::game::battle;
 use crate::location::Location;
-use crate::log;
 use crate::quest;
+use crate::quest::reward::Reward;
+use serde::{Serialize, Serializer};
+use std::collections::VecDeque;
 
-/// This module implements basic event management. It's static: the events are
-/// not subscribed at runtime, but it serves the purpose of decoupling logging
-/// and the quest system from the rest of the codebase.
+/// This module implements basic event management. Events are dispatched to the
+/// subscribers in the game's registry, decoupling logging, quests and
+/// achievements from the rest of the codebase.
 // NOTE these are not exhaustive, and the only included what we already need.
 // In particular, events that are only used for display kind of abuse the fact
 // that we already get a game instance in the handler, so current location and
 // player are omitted
-pub enum Event<'a> {
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Visit {
+        location: Location,
+    },
     EnemyAppears {
-        enemy: &'a Character,
+        #[serde(serialize_with = "serialize_enemy")]
+        enemy: Character,
     },
     Bribe {
         cost: i32,
@@ -23,23 +34,41 @@ pub enum Event<'a> {
         success: bool,
     },
     PlayerAttack {
-        enemy: &'a Character,
+        #[serde(serialize_with = "serialize_enemy")]
+        enemy: Character,
         kind: battle::AttackType,
         damage: i32,
+        affinity: Affinity,
     },
     EnemyAttack {
         kind: battle::AttackType,
         damage: i32,
+        affinity: Affinity,
     },
     StatusEffectDamage {
         damage: i32,
     },
+    EnemyStatusEffectDamage {
+        #[serde(serialize_with = "serialize_enemy")]
+        enemy: Character,
+        damage: i32,
+    },
+    SkillUsed {
+        skill: Skill,
+    },
+    Stunned,
+    /// The confused hero hit itself instead of the enemy.
+    ConfusedAttack {
+        damage: i32,
+    },
     BattleWon {
-        enemy: &'a Character,
+        #[serde(serialize_with = "serialize_enemy")]
+        enemy: Character,
         location: Location,
         xp: i32,
         levels_up: i32,
         gold: i32,
+        items: Vec<String>,
     },
     BattleLost,
     LevelUp {
@@ -50,25 +79,154 @@ pub enum Event<'a> {
         recovered: i32,
         healed: bool,
     },
+    /// The item is the name it's sold by, e.g. `potion`.
     ItemBought {
         item: String,
+        cost: i32,
     },
     ItemUsed {
         item: String,
     },
     ChestFound {
-        items: &'a [String],
+        items: Vec<String>,
         gold: i32,
     },
     TombstoneFound {
-        items: &'a [String],
+        items: Vec<String>,
         gold: i32,
     },
+    QuestCompleted {
+        description: String,
+        reward: Reward,
+    },
+    AchievementUnlocked {
+        achievement: Achievement,
+    },
+    WarpFound {
+        name: String,
+    },
+    Warp {
+        name: String,
+        location: Location,
+        cost: i32,
+    },
+}
+
+/// The character data leaves out the class, so enemies are serialized
+/// along with their class name for the hooks to tell them apart.
+fn serialize_enemy<S: Serializer>(enemy: &Character, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Enemy<'a> {
+        class: String,
+        #[serde(flatten)]
+        character: &'a Character,
+    }
+
+    Enemy {
+        class: enemy.name(),
+        character: enemy,
+    }
+    .serialize(serializer)
 }
 
-impl Event<'_> {
+impl Event {
+    /// Notify the event to every subscriber in the game's registry.
+    /// Events emitted by subscribers while handling another one are queued
+    /// and dispatched after it.
     pub fn emit(game: &mut game::Game, event: Event) {
-        log::handle(game, &event);
-        quest::handle(game, &event);
+        game.events.pending.push_back(event);
+        if game.events.dispatching {
+            return;
+        }
+
+        game.events.dispatching = true;
+        let mut subscribers = std::mem::take(&mut game.events.subscribers);
+        while let Some(event) = game.events.pending.pop_front() {
+            for subscriber in subscribers.iter_mut() {
+                subscriber.handle(game, &event);
+            }
+        }
+        // keep any subscriber registered during the dispatch
+        subscribers.append(&mut game.events.subscribers);
+        game.events.subscribers = subscribers;
+        game.events.dispatching = false;
+    }
+}
+
+/// Receives every event emitted during the game.
+pub trait Subscriber {
+    fn handle(&mut self, game: &mut game::Game, event: &Event);
+}
+
+/// The subscribers notified of the game events. The logging, quests and
+/// achievements handlers are registered by default.
+pub struct Registry {
+    subscribers: Vec<Box<dyn Subscriber>>,
+    pending: VecDeque<Event>,
+    dispatching: bool,
+}
+
+impl Registry {
+    /// A registry without subscribers, for frontends that handle the
+    /// game output on their own.
+    pub fn empty() -> Self {
+        Self {
+            subscribers: Vec::new(),
+            pending: VecDeque::new(),
+            dispatching: false,
+        }
+    }
+
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+}
+
+/// The game rules handlers. The terminal output is subscribed by the binary,
+/// so library users don't get the game printed to stdout.
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.subscribe(Box::new(quest::Handler));
+        registry.subscribe(Box::new(achievement::Handler));
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<i32>>>);
+
+    impl Subscriber for Recorder {
+        fn handle(&mut self, game: &mut game::Game, event: &Event) {
+            if let Event::Bribe { cost } = event {
+                self.0.borrow_mut().push(*cost);
+                // nested events are handled after the current one
+                if *cost > 0 {
+                    Event::emit(game, Event::Bribe { cost: cost - 1 });
+                }
+                self.0.borrow_mut().push(-cost);
+            }
+        }
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let mut game = game::Game::new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        game.events.subscribe(Box::new(Recorder(received.clone())));
+
+        Event::emit(&mut game, Event::Bribe { cost: 2 });
+        assert_eq!(vec![2, -2, 1, -1, 0, 0], *received.borrow());
+
+        // subscribers are kept across resets
+        game.reset();
+        received.borrow_mut().clear();
+        Event::emit(&mut game, Event::Bribe { cost: 0 });
+        assert_eq!(vec![0, 0], *received.borrow());
     }
 }
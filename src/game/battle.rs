@@ -1,10 +1,14 @@
 use super::Game;
+use crate::character::element::{Affinity, Element};
+use crate::character::skill::{Skill, SkillPolicy};
 use crate::character::{Character, Dead, StatusEffect};
 use crate::event::Event;
 use crate::randomizer::Randomizer;
+use serde::Serialize;
 
 /// Outcome of an attack attempt.
 /// This affects primarily how the attack is displayed.
+#[derive(Serialize)]
 pub enum AttackType {
     Regular,
     Critical,
@@ -22,20 +26,29 @@ pub fn run(game: &mut Game, enemy: &mut Character, random: &dyn Randomizer) -> R
     let mut xp = 0;
 
     while !enemy.is_dead() {
-        pl_accum += game.player.speed;
-        en_accum += enemy.speed;
+        pl_accum += game.player.effective_speed();
+        en_accum += enemy.effective_speed();
 
         if pl_accum >= en_accum {
-            if !autopotion(game, enemy) {
-                let new_xp = player_attack(game, enemy, random);
-                xp += new_xp;
+            if game.player.is_stunned() {
+                Event::emit(game, Event::Stunned);
+            } else if !autoheal(game, enemy, random) {
+                if game.player.is_confused() && random.confused_self_attack() {
+                    confused_attack(game, random)?;
+                } else if let Some(skill) = attack_skill(game, enemy) {
+                    xp += skill_attack(game, enemy, skill, random);
+                } else {
+                    let new_xp = player_attack(game, enemy, random);
+                    xp += new_xp;
+                }
             }
 
             game.maybe_receive_status_damage()?;
+            game.tick_effects();
             pl_accum = -1;
         } else {
             enemy_attack(game, enemy, random)?;
-            // TODO enemy receive status effect
+            enemy_status_damage(game, enemy);
             en_accum = -1;
         }
     }
@@ -45,18 +58,91 @@ pub fn run(game: &mut Game, enemy: &mut Character, random: &dyn Randomizer) -> R
 
 /// Attack enemy, returning the gained experience
 fn player_attack(game: &mut Game, enemy: &mut Character, random: &dyn Randomizer) -> i32 {
-    let (attack_type, damage, new_xp) = generate_attack(&game.player, enemy, random);
+    let (attack_type, damage, new_xp, affinity) = generate_attack(&game.player, enemy, random);
+    hit_enemy(game, enemy, attack_type, damage, affinity);
+    new_xp
+}
+
+/// Attack enemy with the given skill, spending its magic points.
+/// Return the gained experience.
+fn skill_attack(
+    game: &mut Game,
+    enemy: &mut Character,
+    skill: Skill,
+    random: &dyn Randomizer,
+) -> i32 {
+    game.player.current_mp -= skill.mp_cost();
+    Event::emit(game, Event::SkillUsed { skill });
+
+    let (attack_type, damage, xp, affinity) = if skill == Skill::FireBolt {
+        // magic attacks ignore the enemy's shield
+        let affinity = enemy.affinity(Element::Fire);
+        let damage = affinity.apply(random.damage(game.player.attack()));
+        let xp = game.player.xp_gained(enemy, damage);
+
+        if affinity != Affinity::Immune && enemy.status_effect.is_none() {
+            enemy.inflict(StatusEffect::Burning);
+            (
+                AttackType::Effect(StatusEffect::Burning),
+                damage,
+                xp,
+                affinity,
+            )
+        } else {
+            (AttackType::Regular, damage, xp, affinity)
+        }
+    } else {
+        let (attack_type, damage, xp, affinity) = generate_attack(&game.player, enemy, random);
+        (attack_type, damage * 2, xp * 2, affinity)
+    };
+
+    hit_enemy(game, enemy, attack_type, damage, affinity);
+    xp
+}
+
+fn hit_enemy(
+    game: &mut Game,
+    enemy: &mut Character,
+    attack_type: AttackType,
+    damage: i32,
+    affinity: Affinity,
+) {
     enemy.receive_damage(damage).unwrap_or_default();
 
     Event::emit(
         game,
         Event::PlayerAttack {
-            enemy,
+            enemy: enemy.clone(),
             kind: attack_type,
             damage,
+            affinity,
         },
     );
-    new_xp
+}
+
+/// Choose an attack skill according to the game's skill policy, if any should be used.
+/// Fire is preferred unless the enemy resists it.
+fn attack_skill(game: &Game, enemy: &Character) -> Option<Skill> {
+    let use_skills = match game.skill_policy {
+        SkillPolicy::Aggressive => true,
+        SkillPolicy::Balanced => enemy.level >= game.player.level,
+        SkillPolicy::Defensive | SkillPolicy::Never => false,
+    };
+    if !use_skills {
+        return None;
+    }
+
+    let fire_works = matches!(
+        enemy.affinity(Element::Fire),
+        Affinity::Weak | Affinity::Normal
+    );
+    if fire_works && game.player.can_use(Skill::FireBolt) {
+        Some(Skill::FireBolt)
+    } else if game.player.can_use(Skill::PowerStrike) {
+        Some(Skill::PowerStrike)
+    } else {
+        None
+    }
 }
 
 /// Attack player, returning Err(Dead) if the player dies.
@@ -65,10 +151,10 @@ fn enemy_attack(
     enemy: &mut Character,
     random: &dyn Randomizer,
 ) -> Result<(), Dead> {
-    let (attack_type, damage, _xp) = generate_attack(enemy, &game.player, random);
+    let (attack_type, damage, _xp, affinity) = generate_attack(enemy, &game.player, random);
     let result = game.player.receive_damage(damage);
     if let AttackType::Effect(status) = attack_type {
-        game.player.status_effect = Some(status);
+        game.player.inflict(status);
     }
 
     Event::emit(
@@ -76,52 +162,96 @@ fn enemy_attack(
         Event::EnemyAttack {
             kind: attack_type,
             damage,
+            affinity,
         },
     );
     result
 }
 
+/// Enemy takes damage from its status effect, if any, and the effect counts down one turn.
+fn enemy_status_damage(game: &mut Game, enemy: &mut Character) {
+    let previous_hp = enemy.current_hp;
+    enemy.receive_status_effect_damage().unwrap_or_default();
+    let damage = previous_hp - enemy.current_hp;
+    if damage > 0 {
+        Event::emit(
+            game,
+            Event::EnemyStatusEffectDamage {
+                enemy: enemy.clone(),
+                damage,
+            },
+        );
+    }
+    enemy.tick_effects();
+}
+
+/// The confused player hits itself instead of the enemy.
+fn confused_attack(game: &mut Game, random: &dyn Randomizer) -> Result<(), Dead> {
+    let damage = random.damage(game.player.damage(&game.player));
+    let result = game.player.receive_damage(damage);
+    Event::emit(game, Event::ConfusedAttack { damage });
+    result
+}
+
 /// Return randomized attack parameters according to the character attributes.
+/// The damage is scaled by the receiver's affinity to the attacker's element.
 fn generate_attack(
     attacker: &Character,
     receiver: &Character,
     random: &dyn Randomizer,
-) -> (AttackType, i32, i32) {
-    let damage = random.damage(attacker.damage(receiver));
+) -> (AttackType, i32, i32, Affinity) {
+    let affinity = receiver.affinity(attacker.attack_element());
+    let damage = affinity.apply(random.damage(attacker.damage(receiver)));
     let xp = attacker.xp_gained(receiver, damage);
 
     let attack_type = random.attack_type(
         attacker.inflicted_status_effect(),
-        attacker.speed,
-        receiver.speed,
+        attacker.effective_speed(),
+        receiver.effective_speed(),
     );
 
     match attack_type {
-        AttackType::Miss => (attack_type, 0, 0),
-        AttackType::Regular => (AttackType::Regular, damage, xp),
-        AttackType::Critical => (attack_type, damage * 2, xp),
+        AttackType::Miss => (attack_type, 0, 0, Affinity::Normal),
+        AttackType::Regular => (AttackType::Regular, damage, xp, affinity),
+        AttackType::Critical => (attack_type, damage * 2, xp, affinity),
         AttackType::Effect(status) if Some(status) != receiver.status_effect => {
-            (attack_type, damage, xp)
+            (attack_type, damage, xp, affinity)
         }
         // don't double-inflict if already has the same status
-        AttackType::Effect(_) => (AttackType::Regular, damage, xp),
+        AttackType::Effect(_) => (AttackType::Regular, damage, xp, affinity),
     }
 }
 
-/// If the player is low on hp and has a potion available use it
-/// instead of attacking in the current turn.
-fn autopotion(game: &mut Game, enemy: &Character) -> bool {
+/// If the player is low on hp use the heal skill, when allowed by the skill policy,
+/// or a potion if available, instead of attacking in the current turn.
+fn autoheal(game: &mut Game, enemy: &Character, random: &dyn Randomizer) -> bool {
     if game.player.current_hp > game.player.max_hp / 3 {
         return false;
     }
 
     // If there's a good chance of winning the battle on the next attack,
-    // don't use the potion.
+    // don't heal.
     let potential_damage = game.player.damage(enemy);
     if potential_damage >= enemy.current_hp {
         return false;
     }
 
+    if game.skill_policy != SkillPolicy::Never && game.player.can_use(Skill::Heal) {
+        game.player.current_mp -= Skill::Heal.mp_cost();
+        Event::emit(game, Event::SkillUsed { skill: Skill::Heal });
+
+        let recovered = game.player.heal(random.damage(game.player.max_hp / 3));
+        Event::emit(
+            game,
+            Event::Heal {
+                item: Some("heal"),
+                recovered,
+                healed: false,
+            },
+        );
+        return true;
+    }
+
     game.use_item("potion").is_ok()
 }
 
@@ -133,8 +263,8 @@ mod tests {
     #[test]
     fn won() {
         let mut game = Game::new();
-        // same level as player
-        let mut enemy = Character::enemy(1, Distance::Near(1));
+        // same level as player, a fixed class so physical attacks are not resisted
+        let mut enemy = Character::enemy_of_class("rat", 1).unwrap();
 
         game.player.speed = 2;
         game.player.current_hp = 20;
@@ -158,7 +288,7 @@ mod tests {
         // extra 100g for the completed quest
         assert_eq!(150, game.gold);
 
-        let mut enemy = Character::enemy(1, Distance::Near(1));
+        let mut enemy = Character::enemy_of_class("rat", 1).unwrap();
         enemy.speed = 1;
         enemy.current_hp = 15;
         enemy.strength = 5;
@@ -173,11 +303,39 @@ mod tests {
         assert_eq!(300, game.gold);
     }
 
+    #[test]
+    fn skills() {
+        let mut game = Game::new();
+        // a fixed class so fire is not resisted
+        let enemy = Character::mimic(2).unwrap();
+
+        // not learned yet
+        assert!(attack_skill(&game, &enemy).is_none());
+
+        game.player.level = Skill::FireBolt.unlock_level();
+        assert!(game.player.learn(Skill::PowerStrike).is_ok());
+        assert!(game.player.learn(Skill::FireBolt).is_ok());
+        game.player.level = 1;
+        game.player.current_mp = 100;
+        assert_eq!(Some(Skill::FireBolt), attack_skill(&game, &enemy));
+
+        game.player.current_mp = Skill::PowerStrike.mp_cost();
+        assert_eq!(Some(Skill::PowerStrike), attack_skill(&game, &enemy));
+
+        game.skill_policy = SkillPolicy::Defensive;
+        assert!(attack_skill(&game, &enemy).is_none());
+
+        // balanced policy doesn't spend mp on weaker enemies
+        game.skill_policy = SkillPolicy::Balanced;
+        game.player.level = 5;
+        assert!(attack_skill(&game, &enemy).is_none());
+    }
+
     #[test]
     fn lost() {
         let mut game = Game::new();
         let near = Distance::Near(1);
-        let mut enemy = Character::enemy(10, near);
+        let mut enemy = Character::enemy(10, near, &game.unlocked).unwrap();
         let result = game.battle(&mut enemy);
         assert!(result.is_err());
     }
@@ -1,34 +1,66 @@
 extern crate dirs;
 
+use crate::achievement::Achievements;
 use crate::character;
+use crate::character::class::Category;
+use crate::character::skill::SkillPolicy;
 use crate::character::Character;
-use crate::event::Event;
+use crate::error::Error;
+use crate::event::{self, Event};
 use crate::item::{Item, Potion};
 use crate::location::Location;
 use crate::quest::QuestList;
 use crate::randomizer::random;
 use crate::randomizer::Randomizer;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tombstone::Tombstone;
 
 pub mod battle;
+pub mod simulation;
 pub mod tombstone;
 
+// the defaults are set per field, so loading a saved game doesn't read
+// the quest packs like Game::new does
 #[derive(Serialize, Deserialize)]
-#[serde(default)]
 pub struct Game {
+    #[serde(default)]
     pub player: Character,
+    #[serde(default = "Location::home")]
     pub location: Location,
+    #[serde(default)]
     pub gold: i32,
+    #[serde(default)]
     pub quests: QuestList,
+    #[serde(default)]
     pub inventory: HashMap<String, Vec<Box<dyn Item>>>,
+    #[serde(default)]
     pub tombstones: HashMap<String, Tombstone>,
+    #[serde(default)]
+    pub skill_policy: SkillPolicy,
+    /// Directories where the hero can rest and shop away from home.
+    #[serde(default)]
+    pub camps: HashSet<Location>,
+    /// Locations the hero can warp to, discovered by beating legendary enemies.
+    #[serde(default)]
+    pub warps: BTreeMap<String, Location>,
+    /// Locked enemy classes that were unlocked by quest rewards.
+    #[serde(default)]
+    pub unlocked: HashSet<String>,
+    #[serde(default)]
     inspected: HashSet<Location>,
+    /// Stored in a file of their own so they survive hard resets.
+    #[serde(skip)]
+    pub achievements: Achievements,
+    #[serde(skip)]
+    pub events: event::Registry,
 }
 
 pub struct ItemNotFound;
 
+pub const CAMP_COST: i32 = 1000;
+const WARP_COST_PER_DIR: i32 = 50;
+
 impl Game {
     pub fn new() -> Self {
         let quests = QuestList::new();
@@ -38,7 +70,13 @@ impl Game {
             gold: 0,
             inventory: HashMap::new(),
             tombstones: HashMap::new(),
+            skill_policy: SkillPolicy::default(),
+            camps: HashSet::new(),
+            warps: BTreeMap::new(),
+            unlocked: HashSet::new(),
             inspected: HashSet::new(),
+            achievements: Achievements::default(),
+            events: event::Registry::default(),
             quests,
         }
     }
@@ -47,9 +85,16 @@ impl Game {
     /// Progress is preserved across games.
     pub fn reset(&mut self) {
         let mut new_game = Self::new();
-        // preserve tombstones and quests across hero's lifes
+        // preserve tombstones, quests, camps, warps, unlocked classes
+        // and achievements across hero's lifes
         std::mem::swap(&mut new_game.tombstones, &mut self.tombstones);
         std::mem::swap(&mut new_game.quests, &mut self.quests);
+        std::mem::swap(&mut new_game.camps, &mut self.camps);
+        std::mem::swap(&mut new_game.warps, &mut self.warps);
+        std::mem::swap(&mut new_game.unlocked, &mut self.unlocked);
+        std::mem::swap(&mut new_game.achievements, &mut self.achievements);
+        std::mem::swap(&mut new_game.events, &mut self.events);
+        new_game.skill_policy = self.skill_policy;
         // TBD shouldn't chests be preserved?
 
         // replace the current, finished game with the new one
@@ -67,7 +112,7 @@ impl Game {
         while self.location != *dest {
             self.visit(self.location.go_to(dest))?;
 
-            if !self.location.is_home() {
+            if !self.is_safe() {
                 if let Some(mut enemy) = self.maybe_spawn_enemy() {
                     return self.maybe_battle(&mut enemy, run, bribe);
                 }
@@ -78,7 +123,9 @@ impl Game {
 
     /// Look for chests and tombstones at the current location.
     /// Remembers previous checks for consistency.
-    pub fn inspect(&mut self) {
+    /// Chests may turn out to be mimics, in which case a battle is started
+    /// and the loot is only obtained if the hero wins.
+    pub fn inspect(&mut self) -> Result<(), character::Dead> {
         self.pick_up_tombstone();
 
         if !self.inspected.contains(&self.location) {
@@ -90,31 +137,65 @@ impl Game {
             match random().range(6) {
                 0 => {
                     let gold = random().gold_gained(self.player.level * 200);
-                    Event::emit(self, Event::ChestFound { items: &[], gold });
-                    self.gold += gold;
+                    self.open_chest(Vec::new(), gold)?;
                 }
                 1 => {
                     let potion = Potion::new(self.player.level);
-                    Event::emit(
-                        self,
-                        Event::ChestFound {
-                            items: &["potion".to_string()],
-                            gold: 0,
-                        },
-                    );
-                    self.add_item("potion", Box::new(potion));
+                    self.open_chest(vec![("potion".to_string(), Box::new(potion))], 0)?;
                 }
                 _ => {}
             }
         }
+        Ok(())
+    }
+
+    /// Add the chest contents to the game, unless it's a mimic that
+    /// kills the hero first.
+    fn open_chest(
+        &mut self,
+        items: Vec<(String, Box<dyn Item>)>,
+        gold: i32,
+    ) -> Result<(), character::Dead> {
+        let distance = self.location.distance_from_home();
+        if !self.is_safe() && random().should_mimic_appear(&distance) {
+            let level = enemy_level(self.player.level, distance.len());
+            if let Some(mut mimic) = Character::mimic(random().enemy_level(level)) {
+                Event::emit(
+                    self,
+                    Event::EnemyAppears {
+                        enemy: mimic.clone(),
+                    },
+                );
+                self.maybe_battle(&mut mimic, false, false)?;
+            }
+        }
+
+        let names = items
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<String>>();
+        Event::emit(self, Event::ChestFound { items: names, gold });
+
+        self.gold += gold;
+        for (name, item) in items {
+            self.add_item(&name, item);
+        }
+        Ok(())
     }
 
     /// Set the hero's location to the one given, and apply related side effects.
     pub fn visit(&mut self, location: Location) -> Result<(), character::Dead> {
         self.location = location;
+        Event::emit(
+            self,
+            Event::Visit {
+                location: self.location.clone(),
+            },
+        );
         if self.location.is_home() {
             let recovered = self.player.heal_full();
             let healed = self.player.maybe_remove_status_effect();
+            self.player.restore_mp();
             Event::emit(
                 self,
                 Event::Heal {
@@ -123,10 +204,40 @@ impl Game {
                     healed,
                 },
             );
+        } else if self.camps.contains(&self.location) {
+            // camps only restore health up to half of the max
+            let missing = self.player.max_hp / 2 - self.player.current_hp;
+            let recovered = self.player.heal(std::cmp::max(0, missing));
+            Event::emit(
+                self,
+                Event::Heal {
+                    item: None,
+                    recovered,
+                    healed: false,
+                },
+            );
         }
 
         // In location is home, already healed of negative status
-        self.maybe_receive_status_damage()
+        self.maybe_receive_status_damage()?;
+        self.tick_effects();
+        Ok(())
+    }
+
+    /// Count down the hero's status effect and buffs by one turn or move.
+    fn tick_effects(&mut self) {
+        if let Some(recovered) = self.player.tick_effects() {
+            if recovered > 0 {
+                Event::emit(
+                    self,
+                    Event::Heal {
+                        item: Some("regen"),
+                        recovered,
+                        healed: false,
+                    },
+                );
+            }
+        }
     }
 
     /// Player takes damage from status_effects, if any.
@@ -137,6 +248,87 @@ impl Game {
         Ok(())
     }
 
+    /// Whether the current location is home or a camp, where enemies
+    /// don't appear and items can be bought.
+    pub fn is_safe(&self) -> bool {
+        self.location.is_home() || self.camps.contains(&self.location)
+    }
+
+    /// Pay to establish a camp at the current location.
+    pub fn set_camp(&mut self) -> Result<(), Error> {
+        if self.is_safe() {
+            return Err(Error::InvalidInput(String::from(
+                "This is already a safe place.",
+            )));
+        }
+        if self.gold < CAMP_COST {
+            return Err(Error::NotEnoughGold);
+        }
+        self.gold -= CAMP_COST;
+        self.camps.insert(self.location.clone());
+        Ok(())
+    }
+
+    /// Remove the camp at the current location.
+    pub fn remove_camp(&mut self) -> Result<(), Error> {
+        if self.camps.remove(&self.location) {
+            Ok(())
+        } else {
+            Err(Error::InvalidInput(String::from("There's no camp here.")))
+        }
+    }
+
+    /// The gold needed to warp from the current location to the given one.
+    pub fn warp_cost(&self, dest: &Location) -> i32 {
+        self.location.distance_from(dest).len() * WARP_COST_PER_DIR
+    }
+
+    /// Pay to move the hero to the warp point with the given name, or home,
+    /// without walking through the directories in between.
+    pub fn warp(&mut self, name: &str) -> Result<(), Error> {
+        let dest = if name == "home" {
+            Location::home()
+        } else {
+            self.warps
+                .get(name)
+                .cloned()
+                .ok_or_else(|| Error::InvalidInput(String::from("Unknown warp point.")))?
+        };
+
+        let cost = self.warp_cost(&dest);
+        if self.gold < cost {
+            return Err(Error::NotEnoughGold);
+        }
+        self.gold -= cost;
+        Event::emit(
+            self,
+            Event::Warp {
+                name: name.to_string(),
+                location: dest.clone(),
+                cost,
+            },
+        );
+        self.visit(dest)?;
+        Ok(())
+    }
+
+    /// Make the current location a warp point, named after its directory.
+    fn discover_warp(&mut self) {
+        if self.location.is_home() || self.warps.values().any(|l| *l == self.location) {
+            return;
+        }
+
+        let base = self.location.name();
+        let mut name = base.clone();
+        let mut count = 1;
+        while name == "home" || self.warps.contains_key(&name) {
+            count += 1;
+            name = format!("{}-{}", base, count);
+        }
+        self.warps.insert(name.clone(), self.location.clone());
+        Event::emit(self, Event::WarpFound { name });
+    }
+
     /// Set the current location to home, and apply related side-effects
     pub fn visit_home(&mut self) {
         self.visit(Location::home()).unwrap_or_default();
@@ -181,13 +373,7 @@ impl Game {
     fn pick_up_tombstone(&mut self) {
         if let Some(mut tombstone) = self.tombstones.remove(&self.location.to_string()) {
             let (items, gold) = tombstone.pick_up(self);
-            Event::emit(
-                self,
-                Event::TombstoneFound {
-                    items: &items,
-                    gold,
-                },
-            );
+            Event::emit(self, Event::TombstoneFound { items, gold });
         }
     }
 
@@ -196,9 +382,14 @@ impl Game {
         if random().should_enemy_appear(&distance) {
             let level = enemy_level(self.player.level, distance.len());
             let level = random().enemy_level(level);
-            let enemy = Character::enemy(level, distance);
+            let enemy = Character::enemy(level, distance, &self.unlocked)?;
 
-            Event::emit(self, Event::EnemyAppears { enemy: &enemy });
+            Event::emit(
+                self,
+                Event::EnemyAppears {
+                    enemy: enemy.clone(),
+                },
+            );
             Some(enemy)
         } else {
             None
@@ -247,15 +438,17 @@ impl Game {
                 let gold = gold_gained(self.player.level, enemy.level);
                 self.gold += gold;
                 let levels_up = self.player.add_experience(xp);
+                let items = self.pick_up_loot(enemy);
 
                 Event::emit(
                     self,
                     Event::BattleWon {
-                        enemy: &enemy,
+                        enemy: enemy.clone(),
                         location: self.location.clone(),
                         xp,
                         levels_up,
                         gold,
+                        items,
                     },
                 );
 
@@ -268,6 +461,9 @@ impl Game {
                     )
                 }
 
+                if enemy.category() == Some(Category::Legendary) {
+                    self.discover_warp();
+                }
                 Ok(())
             }
             Err(character::Dead) => {
@@ -280,6 +476,17 @@ impl Game {
             }
         }
     }
+
+    /// Roll the defeated enemy's drop table and add the resulting items to the game.
+    /// Return the names of the items that were picked up.
+    fn pick_up_loot(&mut self, enemy: &Character) -> Vec<String> {
+        enemy
+            .drops()
+            .iter()
+            .filter(|(_, ratio)| random().loot_drops(*ratio))
+            .filter_map(|(loot, _)| loot.add_to(self, enemy.level))
+            .collect()
+    }
 }
 
 impl Default for Game {
@@ -299,13 +506,24 @@ fn gold_gained(player_level: i32, enemy_level: i32) -> i32 {
 
 #[cfg(test)]
 mod tests {
-    use crate::location::Distance;
+    use crate::location::{filesystem, Distance};
     use item::equipment::Equipment;
 
     use super::*;
     use crate::item;
     use crate::randomizer;
 
+    #[test]
+    fn test_deserialize_defaults() {
+        // missing fields don't load the quests of a new game
+        let game: Game = serde_json::from_str(r#"{"gold": 10}"#).unwrap();
+        assert_eq!(10, game.gold);
+        assert!(game.location.is_home());
+        let (todo, done) = game.quests.list(&game, true);
+        assert!(todo.is_empty());
+        assert!(done.is_empty());
+    }
+
     #[test]
     fn test_enemy_level() {
         // player level 1
@@ -357,6 +575,81 @@ mod tests {
         assert!(game.use_item("potion").is_err());
     }
 
+    #[test]
+    fn test_open_chest() {
+        let mut game = Game::new();
+        let potion = item::Potion::new(1);
+
+        assert!(game
+            .open_chest(vec![("potion".to_string(), Box::new(potion))], 100)
+            .is_ok());
+        // extra 200g for the find chest quest
+        assert_eq!(300, game.gold);
+        assert_eq!(1, *game.inventory().get("potion").unwrap());
+    }
+
+    #[test]
+    fn test_camps() {
+        filesystem::set(filesystem::Memory::new("/home/hero", &["dev/camp"]));
+        let camp = Location::from("~/dev/camp").unwrap();
+        let mut game = Game::new();
+        assert!(game.is_safe());
+        assert!(game.set_camp().is_err());
+
+        game.location = camp.clone();
+        assert!(!game.is_safe());
+        assert_eq!(Err(Error::NotEnoughGold), game.set_camp());
+        game.gold = CAMP_COST;
+        assert!(game.set_camp().is_ok());
+        assert!(game.is_safe());
+        assert_eq!(0, game.gold);
+
+        // camps only heal up to half of the max hp
+        game.player.current_hp = 1;
+        assert!(game.visit(camp.clone()).is_ok());
+        assert_eq!(game.player.max_hp / 2, game.player.current_hp);
+
+        // camps are kept after dying
+        game.reset();
+        assert!(game.camps.contains(&camp));
+        game.location = camp;
+        assert!(game.remove_camp().is_ok());
+        assert!(game.remove_camp().is_err());
+
+        filesystem::set(filesystem::Real::default());
+    }
+
+    #[test]
+    fn test_warps() {
+        filesystem::set(filesystem::Memory::new(
+            "/home/hero",
+            &["dev/rpg", "src/rpg"],
+        ));
+        let dev = Location::from("~/dev/rpg").unwrap();
+        let mut game = Game::new();
+
+        game.location = dev.clone();
+        game.discover_warp();
+        game.discover_warp();
+        game.location = Location::from("~/src/rpg").unwrap();
+        game.discover_warp();
+        assert_eq!(
+            vec!["rpg", "rpg-2"],
+            game.warps.keys().collect::<Vec<&String>>()
+        );
+
+        assert!(game.warp("nowhere").is_err());
+        assert_eq!(200, game.warp_cost(&dev));
+        assert_eq!(Err(Error::NotEnoughGold), game.warp("rpg"));
+        game.gold = 250;
+        assert!(game.warp("rpg").is_ok());
+        assert_eq!(dev, game.location);
+        assert_eq!(50, game.gold);
+        assert_eq!(Err(Error::NotEnoughGold), game.warp("home"));
+
+        filesystem::set(filesystem::Real::default());
+    }
+
     // NOTE: this tests are random and brittle and therefore bad unit tests but they
     // give a reasonable measure of how difficult the game is, so they are better than
     // nothing
@@ -458,7 +751,8 @@ mod tests {
             // duplicate randomization from the game
             let e_level = enemy_level(player_level, distance);
             let e_level = random.enemy_level(e_level);
-            let mut enemy = Character::enemy(e_level, Distance::from(distance));
+            let mut enemy =
+                Character::enemy(e_level, Distance::from(distance), &game.unlocked).unwrap();
 
             if battle::run(&mut game, &mut enemy, &random).is_ok() {
                 wins += 1
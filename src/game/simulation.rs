@@ -0,0 +1,182 @@
+use super::{battle, enemy_level, Game};
+use crate::character::Character;
+use crate::error::Error;
+use crate::event::{self, Event};
+use crate::item::equipment::{Equipment, Shield, Sword};
+use crate::item::Potion;
+use crate::location::Distance;
+use crate::randomizer::{self, DefaultRandomizer, Randomizer};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+const POTIONS: i32 = 10;
+
+/// The results of the simulated battles against one enemy class.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    pub class: String,
+    pub battles: i32,
+    pub wins: i32,
+    /// The sum of the turns of every battle.
+    pub turns: i32,
+    /// The sum of the hero HP left after each won battle.
+    pub hp_left: i32,
+    pub max_hp: i32,
+}
+
+impl Report {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.battles as f64
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.turns as f64 / self.battles as f64
+    }
+
+    /// The average HP left after winning, relative to the hero's max HP.
+    pub fn average_hp_left(&self) -> f64 {
+        if self.wins == 0 {
+            return 0.0;
+        }
+        self.hp_left as f64 / (self.wins * self.max_hp) as f64
+    }
+}
+
+/// Counts the turns taken by both characters during a battle.
+struct TurnCounter(Rc<Cell<i32>>);
+
+impl event::Subscriber for TurnCounter {
+    fn handle(&mut self, _game: &mut Game, event: &Event) {
+        match event {
+            Event::PlayerAttack { .. }
+            | Event::EnemyAttack { .. }
+            | Event::Stunned
+            | Event::ConfusedAttack { .. }
+            // passive regen and remedies don't take the hero's turn
+            | Event::Heal {
+                item: Some("potion") | Some("heal"),
+                ..
+            } => self.0.set(self.0.get() + 1),
+            _ => {}
+        }
+    }
+}
+
+/// Run battles between a hero of the given level, with the equipment and
+/// potions of that level, and the enemies found at the given distance from
+/// home, optionally of a single class. The randomness is seeded so the
+/// results can be compared after balance changes.
+/// Return the results for each enemy class, sorted by name.
+pub fn run(
+    level: i32,
+    distance: i32,
+    runs: i32,
+    class: Option<&str>,
+    seed: u64,
+) -> Result<Vec<Report>, Error> {
+    if class.is_some_and(|name| Character::enemy_of_class(name, 1).is_none()) {
+        return Err(Error::InvalidInput(String::from("Unknown enemy class.")));
+    }
+    randomizer::seed(Some(seed));
+    let random = DefaultRandomizer;
+
+    // quests, achievements and logging are left out of the simulation
+    let mut game = Game::new();
+    game.events = event::Registry::empty();
+    let turns = Rc::new(Cell::new(0));
+    game.events.subscribe(Box::new(TurnCounter(turns.clone())));
+
+    let hero = hero(level);
+    let mut reports = BTreeMap::new();
+    for _ in 0..runs {
+        game.player = hero.clone();
+        game.inventory.clear();
+        for _ in 0..POTIONS {
+            game.add_item("potion", Box::new(Potion::new(level)));
+        }
+
+        // duplicate the enemy level randomization from the game
+        let e_level = random.enemy_level(enemy_level(level, distance));
+        let mut enemy = match class {
+            Some(name) => Character::enemy_of_class(name, e_level),
+            None => Character::enemy(e_level, Distance::from(distance), &game.unlocked),
+        }
+        .unwrap();
+
+        turns.set(0);
+        let won = battle::run(&mut game, &mut enemy, &random).is_ok();
+
+        let report = reports.entry(enemy.name()).or_insert_with(|| Report {
+            class: enemy.name(),
+            max_hp: hero.max_hp,
+            ..Report::default()
+        });
+        report.battles += 1;
+        report.turns += turns.get();
+        if won {
+            report.wins += 1;
+            report.hp_left += game.player.current_hp;
+        }
+    }
+
+    randomizer::seed(None);
+    Ok(reports.into_values().collect())
+}
+
+/// Build a hero of the given level wearing that level's equipment.
+fn hero(level: i32) -> Character {
+    let mut hero = Character::player();
+    while hero.level < level {
+        hero.add_experience(hero.xp_for_next());
+    }
+    hero.sword = Some(Sword::new(level));
+    hero.shield = Some(Shield::new(level));
+    hero
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Subscriber;
+
+    #[test]
+    fn test_simulation() {
+        let reports = run(5, 3, 50, None, 42).unwrap();
+        assert!(!reports.is_empty());
+        assert_eq!(50, reports.iter().map(|r| r.battles).sum::<i32>());
+        for report in &reports {
+            assert!((0.0..=1.0).contains(&report.win_rate()));
+            assert!((0.0..=1.0).contains(&report.average_hp_left()));
+            assert!(report.average_turns() >= 1.0);
+        }
+
+        // the same seed gives the same results
+        assert_eq!(reports, run(5, 3, 50, None, 42).unwrap());
+
+        let reports = run(5, 3, 10, Some("rat"), 1).unwrap();
+        assert_eq!(1, reports.len());
+        assert_eq!("rat", reports[0].class);
+        assert_eq!(10, reports[0].battles);
+
+        assert!(run(5, 3, 10, Some("unicorn"), 1).is_err());
+    }
+
+    #[test]
+    fn test_turn_counter() {
+        let mut game = Game::new();
+        let turns = Rc::new(Cell::new(0));
+        let mut counter = TurnCounter(turns.clone());
+        let heal = |item| Event::Heal {
+            item: Some(item),
+            recovered: 1,
+            healed: false,
+        };
+
+        counter.handle(&mut game, &heal("regen"));
+        assert_eq!(0, turns.get());
+        counter.handle(&mut game, &heal("potion"));
+        counter.handle(&mut game, &heal("heal"));
+        assert_eq!(2, turns.get());
+    }
+}
@@ -33,35 +33,33 @@ impl Tombstone {
 
     /// Add the items of the tombstone to the current game
     pub fn pick_up(&mut self, game: &mut game::Game) -> (Vec<String>, i32) {
-        let mut to_log = Vec::new();
+        let mut found = Vec::new();
 
         // the equipment is picked up only if it's better than the current one
         if let Some(sword) = self.sword.take() {
             if sword.is_upgrade_from(&game.player.sword.as_ref()) {
-                to_log.push(sword.to_string());
+                found.push(sword.to_string());
                 game.player.sword = Some(sword);
             }
         }
 
         if let Some(shield) = self.shield.take() {
             if shield.is_upgrade_from(&game.player.shield.as_ref()) {
-                to_log.push(shield.to_string());
+                found.push(shield.to_string());
                 game.player.shield = Some(shield);
             }
         }
 
         // items and gold are always picked up
         for (name, items) in self.items.drain() {
-            // this is kind of leaking logging logic but well
-            to_log.push(format!("{}x{}", name, items.len()));
-
             for item in items {
+                found.push(name.clone());
                 game.add_item(&name, item);
             }
         }
 
         game.gold += self.gold;
-        (to_log, self.gold)
+        (found, self.gold)
     }
 }
 
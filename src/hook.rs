@@ -0,0 +1,108 @@
+use crate::datafile;
+use crate::event::{self, Event};
+use crate::game::Game;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Runs the user scripts of the hooks directory when their event is emitted,
+/// in file name order when several scripts match the event.
+/// The script gets the event as JSON on stdin, and the event name and hero
+/// status as RPG_* environment variables.
+pub struct Handler {
+    hooks: HashMap<String, Vec<PathBuf>>,
+}
+
+impl Handler {
+    pub fn load() -> Self {
+        Self {
+            hooks: datafile::read_hooks(),
+        }
+    }
+}
+
+impl event::Subscriber for Handler {
+    fn handle(&mut self, game: &mut Game, event: &Event) {
+        if self.hooks.is_empty() {
+            return;
+        }
+
+        // a failure to run the hooks shouldn't interrupt the game
+        let payload = match serde_json::to_value(event) {
+            Ok(payload) => payload,
+            Err(err) => {
+                eprintln!("Failed to serialize event for hooks: {}", err);
+                return;
+            }
+        };
+        let name = payload["event"].as_str().unwrap_or_default();
+        for script in self.hooks.get(name).into_iter().flatten() {
+            if let Err(err) = run(script, game, name, &payload) {
+                eprintln!("Failed to run hook {}: {}", script.display(), err);
+            }
+        }
+    }
+}
+
+/// Run the script and wait for it to finish. Scripts that take long,
+/// e.g. to play a sound, should fork themselves to avoid blocking the game.
+fn run(script: &Path, game: &Game, name: &str, payload: &serde_json::Value) -> io::Result<()> {
+    let player = &game.player;
+    let mut child = Command::new(script)
+        .env("RPG_EVENT", name)
+        .env("RPG_LEVEL", player.level.to_string())
+        .env("RPG_HP", player.current_hp.to_string())
+        .env("RPG_MAX_HP", player.max_hp.to_string())
+        .env("RPG_XP", player.xp.to_string())
+        .env("RPG_GOLD", game.gold.to_string())
+        .env("RPG_LOCATION", game.location.path_string())
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // scripts are not required to read the payload
+        stdin.write_all(payload.to_string().as_bytes()).ok();
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+    use crate::quest::reward::Reward;
+    use serde_json::json;
+
+    #[test]
+    fn test_payload() {
+        let event = Event::LevelUp { current: 3 };
+        assert_eq!(
+            json!({"event": "level_up", "current": 3}),
+            serde_json::to_value(&event).unwrap()
+        );
+
+        let event = Event::QuestCompleted {
+            description: String::from("win a battle"),
+            reward: Reward::Gold(100),
+        };
+        assert_eq!(
+            json!({"event": "quest_completed", "description": "win a battle", "reward": 100}),
+            serde_json::to_value(&event).unwrap()
+        );
+
+        let event = Event::EnemyAppears {
+            enemy: Character::enemy_of_class("rat", 2).unwrap(),
+        };
+        let payload = serde_json::to_value(&event).unwrap();
+        assert_eq!(json!("rat"), payload["enemy"]["class"]);
+        assert_eq!(json!(2), payload["enemy"]["level"]);
+
+        let event = Event::BattleLost;
+        assert_eq!(
+            json!({"event": "battle_lost"}),
+            serde_json::to_value(&event).unwrap()
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use core::fmt;
 
 use crate::character::class as character;
+use crate::character::element::Element;
 use serde::{Deserialize, Serialize};
 
 pub trait Equipment: fmt::Display {
@@ -12,7 +13,7 @@ pub trait Equipment: fmt::Display {
     /// the item is equipped.
     fn strength(&self) -> i32 {
         // get the base strength of the hero at this level
-        let player_strength = character::Class::HERO.strength.at(self.level());
+        let player_strength = character::Class::hero().strength.at(self.level());
 
         // calculate the added strength as a function of the player strength
         (player_strength as f64 * 0.5).round() as i32
@@ -30,22 +31,46 @@ pub trait Equipment: fmt::Display {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sword {
     level: i32,
+    #[serde(default)]
+    element: Option<Element>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Shield {
     level: i32,
+    #[serde(default)]
+    resists: Option<Element>,
+}
+
+impl Sword {
+    /// Build a sword that deals damage of the given element.
+    pub fn with_element(level: i32, element: Element) -> Self {
+        Self {
+            level,
+            element: Some(element),
+        }
+    }
+
+    pub fn element(&self) -> Option<Element> {
+        self.element
+    }
 }
 
 impl fmt::Display for Sword {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(element) = self.element {
+            write!(f, "{} ", element)?;
+        }
         write!(f, "sword[{}]", self.level())
     }
 }
 
 impl Equipment for Sword {
     fn new(level: i32) -> Self {
-        Self { level }
+        Self {
+            level,
+            element: None,
+        }
     }
 
     fn level(&self) -> i32 {
@@ -53,15 +78,35 @@ impl Equipment for Sword {
     }
 }
 
+impl Shield {
+    /// Build a shield that resists damage of the given element.
+    pub fn with_resistance(level: i32, element: Element) -> Self {
+        Self {
+            level,
+            resists: Some(element),
+        }
+    }
+
+    pub fn resists(&self) -> Option<Element> {
+        self.resists
+    }
+}
+
 impl fmt::Display for Shield {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(element) = self.resists {
+            write!(f, "{} ", element)?;
+        }
         write!(f, "shield[{}]", self.level())
     }
 }
 
 impl Equipment for Shield {
     fn new(level: i32) -> Self {
-        Self { level }
+        Self {
+            level,
+            resists: None,
+        }
     }
 
     fn level(&self) -> i32 {
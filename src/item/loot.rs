@@ -0,0 +1,101 @@
+use super::equipment::{Equipment, Shield, Sword};
+use super::{Escape, Potion, Remedy, Tonic};
+use crate::character::element::Element;
+use crate::character::Buff;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Kinds of items that enemies can leave behind after being defeated.
+/// Each class lists the ones it can drop in its drop table.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum Loot {
+    Potion,
+    Remedy,
+    Escape,
+    Tonic(Buff),
+    FireSword,
+    DragonScale,
+}
+
+impl fmt::Display for Loot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loot::Potion => write!(f, "potion"),
+            Loot::Remedy => write!(f, "remedy"),
+            Loot::Escape => write!(f, "escape"),
+            Loot::Tonic(buff) => write!(f, "{}", Tonic::new(*buff)),
+            Loot::FireSword => write!(f, "{} sword", Element::Fire),
+            Loot::DragonScale => write!(f, "{} shield", Element::Fire),
+        }
+    }
+}
+
+impl Loot {
+    /// Build the item at the given level and add it to the game.
+    /// Equipment is only kept if it's better than the current one.
+    /// Return the name of the item if it was picked up.
+    pub fn add_to(&self, game: &mut Game, level: i32) -> Option<String> {
+        match self {
+            Loot::Potion => {
+                game.add_item("potion", Box::new(Potion::new(level)));
+                Some("potion".to_string())
+            }
+            Loot::Remedy => {
+                game.add_item("remedy", Box::new(Remedy::new()));
+                Some("remedy".to_string())
+            }
+            Loot::Escape => {
+                game.add_item("escape", Box::new(Escape::new()));
+                Some("escape".to_string())
+            }
+            Loot::Tonic(buff) => {
+                let tonic = Tonic::new(*buff);
+                let name = tonic.to_string();
+                game.add_item(&name, Box::new(tonic));
+                Some(name)
+            }
+            Loot::FireSword => {
+                let sword = Sword::with_element(level, Element::Fire);
+                if sword.is_upgrade_from(&game.player.sword.as_ref()) {
+                    let name = sword.to_string();
+                    game.player.sword = Some(sword);
+                    return Some(name);
+                }
+                None
+            }
+            Loot::DragonScale => {
+                let shield = Shield::with_resistance(level, Element::Fire);
+                if shield.is_upgrade_from(&game.player.shield.as_ref()) {
+                    let name = shield.to_string();
+                    game.player.shield = Some(shield);
+                    return Some(name);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_to() {
+        let mut game = Game::new();
+
+        assert_eq!(
+            Some("potion".to_string()),
+            Loot::Potion.add_to(&mut game, 1)
+        );
+        assert_eq!(1, *game.inventory().get("potion").unwrap());
+
+        assert!(Loot::DragonScale.add_to(&mut game, 5).is_some());
+        assert_eq!(5, game.player.shield.as_ref().unwrap().level());
+
+        // a lower level shield is not picked up
+        assert!(Loot::DragonScale.add_to(&mut game, 1).is_none());
+        assert_eq!(5, game.player.shield.as_ref().unwrap().level());
+    }
+}
@@ -1,11 +1,13 @@
 use core::fmt;
 
 use crate::character::class as character;
+use crate::character::Buff;
 use crate::event::Event;
 use crate::game;
 use serde::{Deserialize, Serialize};
 
 pub mod equipment;
+pub mod loot;
 pub mod shop;
 
 #[typetag::serde(tag = "type")]
@@ -33,7 +35,7 @@ impl fmt::Display for Potion {
 #[typetag::serde]
 impl Item for Potion {
     fn apply(&self, game: &mut game::Game) {
-        let to_restore = character::Class::HERO.hp.at(self.level) / 2;
+        let to_restore = character::Class::hero().hp.at(self.level) / 2;
         let recovered = game.player.heal(to_restore);
 
         Event::emit(
@@ -51,7 +53,7 @@ impl Item for Potion {
 pub struct Escape {}
 
 impl Escape {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {}
     }
 }
@@ -73,7 +75,7 @@ impl fmt::Display for Escape {
 pub struct Remedy {}
 
 impl Remedy {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {}
     }
 }
@@ -98,3 +100,31 @@ impl fmt::Display for Remedy {
         write!(f, "remedy")
     }
 }
+
+/// Consumable that grants the hero a temporary buff.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Tonic {
+    buff: Buff,
+}
+
+impl Tonic {
+    /// How many turns or moves the buff lasts.
+    const DURATION: i32 = 10;
+
+    pub fn new(buff: Buff) -> Self {
+        Self { buff }
+    }
+}
+
+#[typetag::serde]
+impl Item for Tonic {
+    fn apply(&self, game: &mut game::Game) {
+        game.player.add_buff(self.buff, Self::DURATION);
+    }
+}
+
+impl fmt::Display for Tonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.buff)
+    }
+}
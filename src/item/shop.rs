@@ -1,7 +1,7 @@
 use std::{collections::HashMap, fmt::Display};
 
 use super::equipment::{Equipment, Shield, Sword};
-use crate::character::Character;
+use crate::character::{Buff, Character};
 use crate::event::Event;
 use crate::game::Game;
 use crate::log;
@@ -26,8 +26,15 @@ pub fn buy(game: &mut Game, item: &str) -> Result<(), Error> {
     let mut items = available_items(player)
         .into_iter()
         .collect::<HashMap<String, Box<dyn Shoppable>>>();
-    if let Some(item) = items.remove(item) {
-        item.buy(game)?;
+    if let Some(shoppable) = items.remove(item) {
+        shoppable.buy(game)?;
+        Event::emit(
+            game,
+            Event::ItemBought {
+                item: item.to_string(),
+                cost: shoppable.cost(),
+            },
+        );
         Ok(())
     } else {
         Err(Error::ItemNotAvailable)
@@ -35,7 +42,7 @@ pub fn buy(game: &mut Game, item: &str) -> Result<(), Error> {
 }
 
 /// Build a list of items currently available at the shop
-fn available_items(player: &Character) -> Vec<(String, Box<dyn Shoppable>)> {
+pub fn available_items(player: &Character) -> Vec<(String, Box<dyn Shoppable>)> {
     let mut items = Vec::<(String, Box<dyn Shoppable>)>::new();
     let level = available_level(player);
 
@@ -58,6 +65,11 @@ fn available_items(player: &Character) -> Vec<(String, Box<dyn Shoppable>)> {
     let escape = super::Escape::new();
     items.push(("escape".to_string(), Box::new(escape)));
 
+    for buff in &[Buff::Regen, Buff::Haste, Buff::Might] {
+        let tonic = super::Tonic::new(*buff);
+        items.push((tonic.to_string(), Box::new(tonic)));
+    }
+
     items
 }
 
@@ -76,14 +88,6 @@ pub trait Shoppable: Display {
         }
         game.gold -= self.cost();
         self.add_to(game);
-
-        Event::emit(
-            game,
-            Event::ItemBought {
-                item: self.to_string(),
-            },
-        );
-
         Ok(())
     }
     fn add_to(&self, game: &mut Game);
@@ -138,3 +142,13 @@ impl Shoppable for super::Remedy {
         game.add_item("remedy", Box::new(self.clone()));
     }
 }
+
+impl Shoppable for super::Tonic {
+    fn cost(&self) -> i32 {
+        300
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(&self.to_string(), Box::new(self.clone()));
+    }
+}
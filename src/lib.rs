@@ -0,0 +1,45 @@
+//! The rpg-cli game engine, to embed the game in other tools without
+//! going through the command line interface.
+//!
+//! A game is created with `Game::new`, driven through the `Game`
+//! methods and observed by registering an `event::Subscriber`.
+//!
+//! The terminal output, data files and hook scripts back the binary and
+//! are not part of the API: their modules are only public with the default
+//! `cli` feature, which embedders should disable.
+
+pub mod achievement;
+pub mod character;
+pub mod error;
+pub mod event;
+pub mod game;
+pub mod item;
+pub mod location;
+pub mod quest;
+pub mod randomizer;
+
+#[cfg(feature = "cli")]
+pub mod datafile;
+#[cfg(feature = "cli")]
+pub mod hook;
+#[cfg(feature = "cli")]
+pub mod log;
+
+#[cfg(not(feature = "cli"))]
+#[allow(dead_code)]
+mod datafile;
+#[cfg(not(feature = "cli"))]
+#[allow(dead_code)]
+mod hook;
+#[cfg(not(feature = "cli"))]
+#[allow(dead_code)]
+mod log;
+
+pub use character::Character;
+pub use error::Error;
+pub use event::{Event, Subscriber};
+pub use game::Game;
+pub use item::Item;
+pub use location::Location;
+pub use quest::Quest;
+pub use randomizer::Randomizer;
@@ -0,0 +1,233 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+/// The filesystem operations needed to build and move between locations.
+/// Besides the real filesystem, an in-memory one can be installed to play
+/// or test without depending on the directories of the machine.
+pub trait Filesystem {
+    /// The directory where the hero starts, heals and shops.
+    fn home(&self) -> PathBuf;
+
+    /// Return the absolute form of the path, with `.` and `..` resolved,
+    /// or an error if it doesn't exist.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Return the directories right below the given one.
+    fn subdirectories(&self, path: &Path) -> Vec<PathBuf>;
+}
+
+thread_local! {
+    static FILESYSTEM: RefCell<Rc<dyn Filesystem>> = RefCell::new(Rc::new(Real::default()));
+}
+
+/// Replace the filesystem used by the locations of the current thread.
+pub fn set(filesystem: impl Filesystem + 'static) {
+    FILESYSTEM.with(|current| *current.borrow_mut() = Rc::new(filesystem));
+}
+
+/// Get the filesystem used by the locations of the current thread.
+pub fn get() -> Rc<dyn Filesystem> {
+    FILESYSTEM.with(|current| current.borrow().clone())
+}
+
+/// The filesystem of the machine, with the user's home directory as home
+/// unless another one is given, e.g. the one set by `rpg home set`.
+/// When `RPG_LOGICAL_PATHS` is set, symlinks are kept in the paths like the
+/// shell does, so symlinked directories count as being where they are linked
+/// instead of where they point to.
+pub struct Real {
+    home: PathBuf,
+    logical: bool,
+}
+
+impl Real {
+    pub fn new(home: PathBuf) -> Self {
+        Self {
+            home,
+            logical: std::env::var_os("RPG_LOGICAL_PATHS").is_some(),
+        }
+    }
+}
+
+impl Default for Real {
+    fn default() -> Self {
+        Self::new(dirs::home_dir().unwrap())
+    }
+}
+
+impl Filesystem for Real {
+    fn home(&self) -> PathBuf {
+        self.home.clone()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if !self.logical {
+            // this is a replacement to std::fs::canonicalize()
+            // that circumvents windows quirks with paths
+            return dunce::canonicalize(path);
+        }
+
+        let path = normalize(&logical_current_dir()?.join(path));
+        if path.exists() {
+            Ok(path)
+        } else {
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    fn subdirectories(&self, path: &Path) -> Vec<PathBuf> {
+        match std::fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Return the current directory as seen by the shell, through $PWD, as long
+/// as it points to the actual current directory.
+fn logical_current_dir() -> io::Result<PathBuf> {
+    let physical = std::env::current_dir()?;
+    match std::env::var_os("PWD").map(PathBuf::from) {
+        Some(pwd) if dunce::canonicalize(&pwd).ok().as_ref() == Some(&physical) => Ok(pwd),
+        _ => Ok(physical),
+    }
+}
+
+/// A fixed set of directories that only exist in memory. Relative paths
+/// are resolved from the home directory.
+pub struct Memory {
+    home: PathBuf,
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl Memory {
+    /// Build a filesystem with the given home and directories, along with
+    /// all their ancestors. Relative directories are created below home.
+    pub fn new(home: &str, dirs: &[&str]) -> Self {
+        let home = normalize(Path::new(home));
+        let mut filesystem = Self {
+            home: home.clone(),
+            dirs: BTreeSet::new(),
+        };
+        filesystem.create_dir_all(&home);
+        for dir in dirs {
+            filesystem.create_dir_all(&home.join(dir));
+        }
+        filesystem
+    }
+
+    fn create_dir_all(&mut self, path: &Path) {
+        for ancestor in normalize(path).ancestors() {
+            self.dirs.insert(ancestor.to_path_buf());
+        }
+    }
+}
+
+impl Filesystem for Memory {
+    fn home(&self) -> PathBuf {
+        self.home.clone()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize(&self.home.join(path));
+        if self.dirs.contains(&path) {
+            Ok(path)
+        } else {
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    fn subdirectories(&self, path: &Path) -> Vec<PathBuf> {
+        self.dirs
+            .iter()
+            .filter(|dir| dir.parent() == Some(path))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Resolve the `.` and `..` components of the path without accessing
+/// the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            other => normal.push(other.as_os_str()),
+        }
+    }
+    normal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_logical_paths() {
+        let dir = std::env::temp_dir().join(format!("rpg-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("target/project")).unwrap();
+        std::os::unix::fs::symlink(dir.join("target/project"), dir.join("link")).unwrap();
+        let link = dir.join("link");
+
+        let physical = Real {
+            home: dir.clone(),
+            logical: false,
+        };
+        assert_eq!(
+            dunce::canonicalize(dir.join("target/project")).unwrap(),
+            physical.canonicalize(&link).unwrap()
+        );
+        // going up from the link physically goes up from its target
+        assert!(physical.canonicalize(&link.join("../link")).is_err());
+
+        let logical = Real {
+            home: dir.clone(),
+            logical: true,
+        };
+        assert_eq!(link, logical.canonicalize(&link).unwrap());
+        assert_eq!(link, logical.canonicalize(&link.join("../link")).unwrap());
+        assert!(logical.canonicalize(&dir.join("missing")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_memory() {
+        let filesystem = Memory::new("/home/hero", &["dev/rpg", "/tmp"]);
+        let canonicalize = |path| filesystem.canonicalize(Path::new(path));
+
+        assert_eq!(PathBuf::from("/home/hero"), filesystem.home());
+        assert_eq!(
+            PathBuf::from("/home/hero/dev"),
+            canonicalize("dev").unwrap()
+        );
+        assert_eq!(
+            PathBuf::from("/home/hero/dev"),
+            canonicalize("/home/hero/./dev/rpg/..").unwrap()
+        );
+        assert_eq!(PathBuf::from("/"), canonicalize("../..").unwrap());
+        assert!(canonicalize("/tmp").is_ok());
+        assert!(canonicalize("/usr").is_err());
+        assert!(canonicalize("dev/other").is_err());
+
+        assert_eq!(
+            vec![PathBuf::from("/home"), PathBuf::from("/tmp")],
+            filesystem.subdirectories(Path::new("/"))
+        );
+        assert!(filesystem
+            .subdirectories(Path::new("/home/hero/dev/rpg"))
+            .is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::path;
 
+pub mod filesystem;
+
 #[derive(Serialize, Deserialize, Debug, Eq, Clone)]
 pub struct Location {
     path: path::PathBuf,
@@ -10,18 +12,16 @@ impl Location {
     /// Build a location from the given path string.
     /// The path is validated to exist and converted to it's canonical form.
     pub fn from(path: &str) -> Result<Self, std::io::Error> {
+        let filesystem = filesystem::get();
         // if input doesn't come from shell, we want to interpret ~ as home ourselves
         let mut path = patch_oldpwd(path);
         if path.starts_with('~') {
             // TODO figure out these string lossy stuff
-            let home_str = dirs::home_dir().unwrap().to_string_lossy().to_string();
+            let home_str = filesystem.home().to_string_lossy().to_string();
             path = path.replacen("~", &home_str, 1)
         }
 
-        let path = path::Path::new(&path);
-        // this is a replacement to std::fs::canonicalize()
-        // that circumvents windows quirks with paths
-        let path = dunce::canonicalize(&path)?;
+        let path = filesystem.canonicalize(path::Path::new(&path))?;
         Ok(Self { path })
     }
 
@@ -31,32 +31,64 @@ impl Location {
 
     pub fn home() -> Self {
         Self {
-            path: dirs::home_dir().unwrap(),
+            path: filesystem::get().home(),
         }
     }
 
     pub fn is_home(&self) -> bool {
-        self.path == dirs::home_dir().unwrap()
+        self.path == filesystem::get().home()
     }
 
     /// Return a new location that it's one dir closer to the given destination.
+    /// When the locations are on different roots, e.g. drives on Windows,
+    /// the hero walks up to its root and then jumps to the destination's one.
     pub fn go_to(&self, dest: &Self) -> Self {
         let next = if dest.path.starts_with(&self.path) {
             let self_len = self.path.components().count();
             dest.path.components().take(self_len + 1).collect()
+        } else if let Some(parent) = parent(&self.path) {
+            parent.to_path_buf()
         } else {
-            self.path.parent().unwrap().to_path_buf()
+            root(&dest.path).to_path_buf()
         };
         Self { path: next }
     }
 
-    fn distance_from(&self, other: &Self) -> Distance {
+    /// Return the directory containing this location, if any.
+    pub fn parent(&self) -> Option<Self> {
+        self.path.parent().map(|path| Self {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Return the directories right below this location, sorted by name.
+    pub fn children(&self) -> Vec<Self> {
+        let mut children = filesystem::get()
+            .subdirectories(&self.path)
+            .into_iter()
+            .map(|path| Self { path })
+            .collect::<Vec<_>>();
+        children.sort_by(|a, b| a.path.cmp(&b.path));
+        children
+    }
+
+    /// Return the name of the last directory of the path.
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path_string())
+    }
+
+    /// Return how many directories apart the locations are.
+    pub fn distance_from(&self, other: &Self) -> Distance {
         let mut current = self.path.as_path();
         let dest = other.path.as_path();
 
         let mut distance = 0;
-        while !dest.starts_with(&current) {
-            current = current.parent().unwrap();
+        while !dest.starts_with(current) {
+            // switching roots counts as one more step
+            current = parent(current).unwrap_or_else(|| root(dest));
             distance += 1;
         }
         let dest = dest.strip_prefix(current).unwrap();
@@ -69,6 +101,21 @@ impl Location {
     }
 }
 
+/// Return the root of the path, e.g. `/` or `C:\`. The first directory
+/// is the root of relative paths.
+fn root(path: &path::Path) -> &path::Path {
+    path.ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .last()
+        .unwrap_or(path)
+}
+
+/// Return the parent of the path, if it's not a root.
+fn parent(path: &path::Path) -> Option<&path::Path> {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+}
+
 /// To match the `cd` behavior, when the path '-' is passed try to
 /// go to the previous location based on $OLDPWD.
 /// If that env var is missing go home.
@@ -98,7 +145,7 @@ impl std::hash::Hash for Location {
 
 impl std::fmt::Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let home = dirs::home_dir().unwrap().to_string_lossy().to_string();
+        let home = filesystem::get().home().to_string_lossy().to_string();
         let mut loc = self.path.to_string_lossy().replace(&home, "~");
         if loc == "~" {
             loc = "home".to_string();
@@ -124,7 +171,7 @@ impl Distance {
         }
     }
 
-    pub fn len(&self) -> i32 {
+    pub(crate) fn len(&self) -> i32 {
         match self {
             Distance::Near(s) => *s,
             Distance::Mid(s) => *s,
@@ -145,11 +192,35 @@ mod tests {
             Location::from("~/.").unwrap(),
             Location::from("~/").unwrap()
         );
-        // FIXME this only works if /usr/bin exists
-        // assert_eq!(
-        //     Location::from("/usr").unwrap(),
-        //     Location::from("/usr/bin/../").unwrap()
-        // );
+    }
+
+    #[test]
+    fn test_memory_filesystem() {
+        filesystem::set(filesystem::Memory::new(
+            "/home/hero",
+            &["dev/rpg", "/usr/bin"],
+        ));
+
+        assert_eq!(
+            Location::from("/usr").unwrap(),
+            Location::from("/usr/bin/../").unwrap()
+        );
+        assert!(Location::from("/usr/lib").is_err());
+        assert!(Location::from("~").unwrap().is_home());
+        assert_eq!("~/dev/rpg", Location::from("dev/rpg").unwrap().to_string());
+        assert_eq!(
+            2,
+            Location::from("~/dev/rpg")
+                .unwrap()
+                .distance_from_home()
+                .len()
+        );
+        assert_eq!(
+            vec![Location::from("~/dev").unwrap()],
+            Location::home().children()
+        );
+
+        filesystem::set(filesystem::Real::default());
     }
 
     #[test]
@@ -199,6 +270,39 @@ mod tests {
         assert_eq!(Location::home().distance_from_home().len(), 0);
     }
 
+    #[test]
+    fn test_root_switch() {
+        assert_eq!(path::Path::new("/"), root(path::Path::new("/usr/bin")));
+        assert_eq!(path::Path::new("dev"), root(path::Path::new("dev/rpg")));
+
+        // relative paths stand for different roots, like the drives on windows
+        let source = location_from("users/facundo");
+        let dest = location_from("dev/rpg");
+        assert_eq!(3, source.distance_from(&dest).len());
+
+        let source = source.go_to(&dest);
+        assert_eq!(location_from("users"), source);
+        let source = source.go_to(&dest);
+        assert_eq!(location_from("dev"), source);
+        let source = source.go_to(&dest);
+        assert_eq!(dest, source);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_different_roots() {
+        let source = location_from("C:\\Users\\facundo");
+        let dest = location_from("D:\\dev\\rpg");
+        assert_eq!(5, source.distance_from(&dest).len());
+
+        let source = source.go_to(&dest);
+        assert_eq!(location_from("C:\\Users"), source);
+        let source = source.go_to(&dest).go_to(&dest);
+        assert_eq!(location_from("D:\\"), source);
+        let source = source.go_to(&dest).go_to(&dest);
+        assert_eq!(dest, source);
+    }
+
     /// test-only equivalent for Location::from, specifically to bypass
     /// path existence checks.
     fn location_from(path: &str) -> Location {
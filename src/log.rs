@@ -1,9 +1,15 @@
+use crate::achievement::Achievement;
+use crate::character::element::Affinity;
+use crate::character::skill::Skill;
 use crate::character::{Character, StatusEffect};
-use crate::event::Event;
+use crate::event::{self, Event};
 use crate::game::battle::AttackType;
+use crate::game::simulation;
 use crate::game::Game;
 use crate::item::shop;
 use crate::location::Location;
+use crate::quest::reward::Reward;
+use crate::quest::Entry;
 use colored::*;
 use once_cell::sync::OnceCell;
 
@@ -26,7 +32,16 @@ fn plain() -> bool {
     *PLAIN.get().unwrap_or(&false)
 }
 
-pub fn handle(game: &Game, event: &Event) {
+/// Prints the events as they happen.
+pub struct Handler;
+
+impl event::Subscriber for Handler {
+    fn handle(&mut self, game: &mut Game, event: &Event) {
+        handle(game, event);
+    }
+}
+
+fn handle(game: &Game, event: &Event) {
     match event {
         Event::EnemyAppears { enemy } => {
             enemy_appears(enemy, &game.location);
@@ -35,22 +50,40 @@ pub fn handle(game: &Game, event: &Event) {
             enemy,
             kind,
             damage,
+            affinity,
         } => {
-            attack(enemy, kind, *damage);
+            attack(enemy, kind, *damage, *affinity);
         }
-        Event::EnemyAttack { kind, damage } => {
-            attack(&game.player, kind, *damage);
+        Event::EnemyAttack {
+            kind,
+            damage,
+            affinity,
+        } => {
+            attack(&game.player, kind, *damage, *affinity);
         }
         Event::StatusEffectDamage { damage } => {
             status_effect_damage(&game.player, *damage);
         }
+        Event::EnemyStatusEffectDamage { enemy, damage } => {
+            status_effect_damage(enemy, *damage);
+        }
+        Event::SkillUsed { skill } => {
+            skill_used(&game.player, *skill);
+        }
+        Event::Stunned => {
+            stunned(&game.player);
+        }
+        Event::ConfusedAttack { damage } => {
+            confused_attack(&game.player, *damage);
+        }
         Event::BattleWon {
             xp,
             levels_up,
             gold,
+            items,
             ..
         } => {
-            battle_won(&game, *xp, *levels_up, *gold);
+            battle_won(&game, *xp, *levels_up, *gold, items);
         }
         Event::BattleLost => {
             battle_lost(&game.player);
@@ -61,6 +94,18 @@ pub fn handle(game: &Game, event: &Event) {
         Event::TombstoneFound { items, gold } => {
             tombstone(items, *gold);
         }
+        Event::QuestCompleted { reward, .. } => {
+            quest_done(reward);
+        }
+        Event::AchievementUnlocked { achievement } => {
+            achievement_unlocked(*achievement);
+        }
+        Event::WarpFound { name } => {
+            warp_found(name);
+        }
+        Event::Warp { location, cost, .. } => {
+            warp(&game.player, location, *cost);
+        }
         Event::Bribe { cost } => {
             bribe(&game.player, *cost);
         }
@@ -81,6 +126,7 @@ pub fn handle(game: &Game, event: &Event) {
         } => {
             heal(&game.player, &game.location, *recovered, *healed);
         }
+        Event::Visit { .. } => {}
         Event::LevelUp { .. } => {}
         Event::ItemBought { .. } => {}
         Event::ItemUsed { .. } => {}
@@ -98,6 +144,39 @@ pub fn status(game: &Game) {
     }
 }
 
+/// Fill the placeholders of the format string, e.g. `{name}[{level}]{hp_bar}`,
+/// with the hero status. Bars are not colored so they are safe to use in shell prompts.
+pub fn format_prompt(game: &Game, format: &str) -> String {
+    let player = &game.player;
+    let status = player
+        .status_effect
+        .map(|s| status_effect_params(s).0)
+        .unwrap_or_default();
+
+    let values = [
+        ("{name}", player.name()),
+        ("{level}", player.level.to_string()),
+        ("{hp}", player.current_hp.to_string()),
+        ("{max_hp}", player.max_hp.to_string()),
+        ("{hp_bar}", plain_bar(4, player.current_hp, player.max_hp)),
+        ("{mp}", player.current_mp.to_string()),
+        ("{max_mp}", player.max_mp.to_string()),
+        ("{mp_bar}", plain_bar(4, player.current_mp, player.max_mp)),
+        ("{xp}", player.xp.to_string()),
+        ("{next_xp}", player.xp_for_next().to_string()),
+        ("{xp_bar}", plain_bar(4, player.xp, player.xp_for_next())),
+        ("{gold}", game.gold.to_string()),
+        ("{location}", game.location.to_string()),
+        ("{status}", status.to_string()),
+    ];
+
+    let mut prompt = format.to_string();
+    for (placeholder, value) in &values {
+        prompt = prompt.replace(placeholder, value);
+    }
+    prompt
+}
+
 pub fn shop_list(game: &Game, items: Vec<Box<dyn shop::Shoppable>>) {
     for item in items {
         let display = format!("{}", item);
@@ -107,18 +186,200 @@ pub fn shop_list(game: &Game, items: Vec<Box<dyn shop::Shoppable>>) {
     println!("\n    funds: {}", format_gold(game.gold));
 }
 
-pub fn quest_list(todo: &[String], done: &[String]) {
+/// Print the win rate, average turns and HP left of the simulated
+/// battles against each enemy class.
+pub fn simulation(reports: &[simulation::Report]) {
+    if plain() {
+        for report in reports {
+            println!(
+                "{}\t{}\t{:.3}\t{:.1}\t{:.3}",
+                report.class,
+                report.battles,
+                report.win_rate(),
+                report.average_turns(),
+                report.average_hp_left()
+            );
+        }
+        return;
+    }
+
+    println!(
+        "    {:<12} {:>7} {:>7} {:>7} {:>7}",
+        "class", "battles", "wins", "turns", "hp left"
+    );
+    for report in reports {
+        let win_rate = format!("{:.1}%", report.win_rate() * 100.0);
+        let win_rate = if report.win_rate() < 0.5 {
+            win_rate.red()
+        } else {
+            win_rate.green()
+        };
+        println!(
+            "    {:<12} {:>7} {:>7} {:>7.1} {:>6.0}%",
+            report.class,
+            report.battles,
+            win_rate,
+            report.average_turns(),
+            report.average_hp_left() * 100.0
+        );
+    }
+}
+
+/// Print the warp points along with the cost to warp to each of them.
+pub fn warp_list(game: &Game) {
+    let home = Location::home();
+    let warps = std::iter::once(("home", &home)).chain(
+        game.warps
+            .iter()
+            .map(|(name, location)| (name.as_str(), location)),
+    );
+    for (name, location) in warps {
+        println!(
+            "    {:<12} {:<6} {}",
+            name,
+            format_gold(game.warp_cost(location)),
+            location
+        );
+    }
+}
+
+/// Print the locations of the hero's camps.
+pub fn camp_list(game: &Game) {
+    let mut camps = game
+        .camps
+        .iter()
+        .map(|camp| camp.to_string())
+        .collect::<Vec<String>>();
+    camps.sort();
+    for camp in camps {
+        println!("    {}", camp);
+    }
+}
+
+/// Print the skills learned by the hero, and the ones that can be learned
+/// or will be unlocked at higher levels.
+pub fn skill_list(game: &Game) {
+    let player = &game.player;
+    for skill in Skill::ALL {
+        let cost = format!("{}mp", skill.mp_cost());
+        let line = format!("{:<14} {:<5}", skill.to_string(), cost);
+        if player.skills.contains(skill) {
+            println!("  {} {}", "✔".green(), line);
+        } else if player.level >= skill.unlock_level() {
+            println!("  {} {} learnable", "□".dimmed(), line);
+        } else {
+            let locked = format!("level {}", skill.unlock_level());
+            println!("  {} {} {}", "□".dimmed(), line.dimmed(), locked.dimmed());
+        }
+    }
+    println!("\n    policy: {}", game.skill_policy);
+}
+
+/// Print the quest TODO list. In detailed mode a progress bar and the pending
+/// items of each quest are shown, and locked quests are included.
+pub fn quest_list(todo: &[Entry], done: &[String], detailed: bool) {
     for quest in todo {
-        println!("  {} {}", "□".dimmed(), quest);
+        let progress = &quest.progress;
+        let count = if progress.target > 1 {
+            format!(" {}/{}", progress.current, progress.target)
+        } else {
+            String::new()
+        };
+        let reward = format_reward(&quest.reward);
+
+        if quest.locked {
+            let line = format!("{}{} (level {})", quest.description, count, quest.unlock_at);
+            println!("  {} {} {}", "□".dimmed(), line.dimmed(), reward);
+        } else if detailed {
+            let bar = bar_display(
+                10,
+                progress.current,
+                progress.target,
+                "green",
+                "bright black",
+            );
+            println!(
+                "  {} {} {}{} {}",
+                "□".dimmed(),
+                quest.description,
+                bar,
+                count,
+                reward
+            );
+        } else {
+            println!(
+                "  {} {}{} {}",
+                "□".dimmed(),
+                quest.description,
+                count,
+                reward
+            );
+        }
+
+        if detailed && !progress.remaining.is_empty() {
+            println!("      {}", progress.remaining.join(", ").dimmed());
+        }
     }
     for quest in done {
         println!("  {} {}", "✔".green(), quest.dimmed());
     }
 }
 
-pub fn quest_done(reward: i32) {
+fn quest_done(reward: &Reward) {
+    if !quiet() {
+        println!("    {} quest completed!", format_reward(reward));
+    }
+}
+
+fn warp_found(name: &str) {
+    if !quiet() {
+        println!("    {} warp point found: {}", "✦".cyan(), name);
+    }
+}
+
+fn warp(player: &Character, location: &Location, cost: i32) {
+    let suffix = format!("warped {}", format!("-{}g", cost).yellow());
+    log(player, location, &suffix);
+}
+
+fn achievement_unlocked(achievement: Achievement) {
     if !quiet() {
-        println!("    {} quest completed!", format_gold_plus(reward));
+        println!(
+            "    {} achievement unlocked: {}",
+            "★".yellow(),
+            achievement.description()
+        );
+    }
+}
+
+pub fn achievements(list: &[(Achievement, Option<String>)]) {
+    for (achievement, date) in list {
+        if let Some(date) = date {
+            println!(
+                "  {} {} {}",
+                "★".yellow(),
+                achievement.description(),
+                date.dimmed()
+            );
+        } else {
+            println!("  {} {}", "☆".dimmed(), achievement.description().dimmed());
+        }
+    }
+}
+
+pub fn format_reward(reward: &Reward) -> String {
+    match reward {
+        Reward::Gold(gold) => format_gold_plus(*gold).to_string(),
+        Reward::Xp { .. } => reward.to_string().bold().to_string(),
+        Reward::Item { .. } => reward.to_string(),
+        Reward::Bonus { .. } => reward.to_string().cyan().to_string(),
+        Reward::Skill { .. } => reward.to_string().blue().to_string(),
+        Reward::Unlock { .. } => reward.to_string().red().to_string(),
+        Reward::Many(rewards) => rewards
+            .iter()
+            .map(format_reward)
+            .collect::<Vec<String>>()
+            .join(" "),
     }
 }
 
@@ -177,9 +438,14 @@ fn heal_item(player: &Character, item: &str, recovered: i32, healed: bool) {
     }
 }
 
-fn attack(character: &Character, attack: &AttackType, damage: i32) {
+fn attack(character: &Character, attack: &AttackType, damage: i32, affinity: Affinity) {
     if !quiet() {
-        battle_log(character, &format_attack(character, &attack, damage));
+        let suffix = format!(
+            "{}{}",
+            format_attack(character, &attack, damage),
+            format_affinity(affinity)
+        );
+        battle_log(character, &suffix);
     }
 }
 
@@ -188,11 +454,31 @@ fn status_effect_damage(character: &Character, damage: i32) {
     battle_log(character, &format_damage(character, damage, &emoji));
 }
 
+fn skill_used(player: &Character, skill: Skill) {
+    if !quiet() {
+        let cost = format!("-{}mp", skill.mp_cost()).blue();
+        battle_log(player, &format!("{} {}!", cost, skill));
+    }
+}
+
+fn stunned(player: &Character) {
+    let (_, emoji) = status_effect_params(StatusEffect::Stunned);
+    battle_log(player, &format!("{} can't move!", emoji));
+}
+
+fn confused_attack(player: &Character, damage: i32) {
+    let (_, emoji) = status_effect_params(StatusEffect::Confused);
+    battle_log(
+        player,
+        &format!("{} hit itself!", format_damage(player, damage, emoji)),
+    );
+}
+
 fn battle_lost(player: &Character) {
     battle_log(player, "\u{1F480}");
 }
 
-fn battle_won(game: &Game, xp: i32, levels_up: i32, gold: i32) {
+fn battle_won(game: &Game, xp: i32, levels_up: i32, gold: i32, items: &[String]) {
     let level_str = if levels_up > 0 {
         let plus = (0..levels_up).map(|_| "+").collect::<String>();
         format!(" {}level", plus).cyan().to_string()
@@ -200,13 +486,19 @@ fn battle_won(game: &Game, xp: i32, levels_up: i32, gold: i32) {
         "".to_string()
     };
 
+    let items_str = items
+        .iter()
+        .map(|item| format!(" +{}", item))
+        .collect::<String>();
+
     battle_log(
         &game.player,
         &format!(
-            "{}{} {}",
+            "{}{} {}{}",
             format!("+{}xp", xp).bold(),
             level_str,
-            format_gold_plus(gold)
+            format_gold_plus(gold),
+            items_str
         ),
     );
     short_status(game);
@@ -229,14 +521,23 @@ fn long_status(game: &Game) {
         player.xp,
         player.xp_for_next()
     );
+    println!(
+        "    mp:{} {}/{}",
+        mp_display(player, 10),
+        player.current_mp,
+        player.max_mp
+    );
     if let Some(status) = player.status_effect {
         println!("    status: {}", format_status_effect(status).bright_red());
     }
+    if !player.buffs.is_empty() {
+        println!("    buffs: {}", format_buffs(player).green());
+    }
     println!(
         "    att:{}   def:{}   spd:{}",
         player.attack(),
         player.deffense(),
-        player.speed
+        player.effective_speed()
     );
     println!("    {}", format_equipment(player));
     println!("    {}", format_inventory(game));
@@ -265,19 +566,28 @@ fn plain_status(game: &Game) {
         String::new()
     };
 
+    let buffs = if player.buffs.is_empty() {
+        String::new()
+    } else {
+        format!("buffs:{}\t", format_buffs(player))
+    };
+
     println!(
-        "{}[{}]\t@{}\thp:{}/{}\txp:{}/{}\tatt:{}\tdef:{}\tspd:{}\t{}{}\t{}\tg:{}",
+        "{}[{}]\t@{}\thp:{}/{}\tmp:{}/{}\txp:{}/{}\tatt:{}\tdef:{}\tspd:{}\t{}{}{}\t{}\tg:{}",
         player.name(),
         player.level,
         game.location,
         player.current_hp,
         player.max_hp,
+        player.current_mp,
+        player.max_mp,
         player.xp,
         player.xp_for_next(),
         player.attack(),
         player.deffense(),
-        player.speed,
+        player.effective_speed(),
         status_effect,
+        buffs,
         format_equipment(player),
         format_inventory(game),
         game.gold
@@ -289,7 +599,19 @@ fn chest(items: &[String], gold: i32) {
 }
 
 fn tombstone(items: &[String], gold: i32) {
-    format_ls("\u{1FAA6}", items, gold);
+    // group repeated items, e.g. potionx3
+    let mut counts: Vec<(&String, usize)> = Vec::new();
+    for item in items {
+        match counts.iter_mut().find(|(name, _)| *name == item) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((item, 1)),
+        }
+    }
+    let items = counts
+        .iter()
+        .map(|(name, count)| format!("{}x{}", name, count))
+        .collect::<Vec<String>>();
+    format_ls("\u{1FAA6}", &items, gold);
 }
 
 fn format_ls(emoji: &str, items: &[String], gold: i32) {
@@ -337,7 +659,7 @@ fn format_character(character: &Character) -> String {
     format!("{}[{}]", name, character.level)
 }
 
-fn format_equipment(character: &Character) -> String {
+pub fn format_equipment(character: &Character) -> String {
     let mut fragments = Vec::new();
 
     if let Some(sword) = &character.sword {
@@ -350,6 +672,17 @@ fn format_equipment(character: &Character) -> String {
     format!("equip:{{{}}}", fragments.join(","))
 }
 
+pub fn format_buffs(character: &Character) -> String {
+    let mut buffs = character
+        .buffs
+        .iter()
+        .map(|(buff, turns)| format!("{}({})", buff, turns))
+        .collect::<Vec<String>>();
+
+    buffs.sort();
+    buffs.join(" ")
+}
+
 pub fn format_inventory(game: &Game) -> String {
     let mut items = game
         .inventory()
@@ -372,6 +705,15 @@ fn format_attack(receiver: &Character, attack: &AttackType, damage: i32) -> Stri
     }
 }
 
+fn format_affinity(affinity: Affinity) -> String {
+    match affinity {
+        Affinity::Weak => " weak!".bold().to_string(),
+        Affinity::Resist => " resist".dimmed().to_string(),
+        Affinity::Immune => " immune".dimmed().to_string(),
+        Affinity::Normal => String::new(),
+    }
+}
+
 fn format_damage(receiver: &Character, amount: i32, suffix: &str) -> String {
     let color = if receiver.is_player() {
         "bright red".to_string()
@@ -390,6 +732,9 @@ fn status_effect_params(status_effect: StatusEffect) -> (&'static str, &'static
     match status_effect {
         StatusEffect::Burning => ("burning", "\u{1F525}"),
         StatusEffect::Poisoned => ("poisoned", "\u{2620}\u{FE0F} "),
+        StatusEffect::Stunned => ("stunned", "\u{1F4AB}"),
+        StatusEffect::Slowed => ("slowed", "\u{1F40C}"),
+        StatusEffect::Confused => ("confused", "\u{1F635}"),
     }
 }
 
@@ -403,6 +748,16 @@ fn hp_display(character: &Character, slots: i32) -> String {
     )
 }
 
+fn mp_display(character: &Character, slots: i32) -> String {
+    bar_display(
+        slots,
+        character.current_mp,
+        character.max_mp,
+        "blue",
+        "bright black",
+    )
+}
+
 fn xp_display(character: &Character, slots: i32) -> String {
     if character.is_player() {
         bar_display(
@@ -437,8 +792,22 @@ fn bar_display(
     format!("[{}{}]", current, missing)
 }
 
+fn plain_bar(slots: i32, current: i32, total: i32) -> String {
+    if total <= 0 {
+        return String::new();
+    }
+    let (filled, rest) = bar_slots(slots, total, current);
+    format!(
+        "[{}{}]",
+        "x".repeat(filled as usize),
+        "-".repeat(rest as usize)
+    )
+}
+
 fn bar_slots(slots: i32, total: i32, current: i32) -> (i32, i32) {
     let units = (current as f64 * slots as f64 / total as f64).ceil() as i32;
+    // points can go over the total, e.g. with buffs
+    let units = units.clamp(0, slots);
     (units, slots - units)
 }
 
@@ -454,6 +823,26 @@ fn format_gold_plus(gold: i32) -> ColoredString {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_prompt() {
+        let mut game = Game::new();
+        game.gold = 25;
+        game.player.current_hp = game.player.max_hp / 2;
+
+        assert_eq!(
+            "hero[1][xx--] 25g@home",
+            format_prompt(&game, "{name}[{level}]{hp_bar} {gold}g@{location}")
+        );
+        let hp = format!("{}/{}", game.player.current_hp, game.player.max_hp);
+        assert_eq!(hp, format_prompt(&game, "{hp}/{max_hp}"));
+        assert_eq!("{unknown}", format_prompt(&game, "{unknown}"));
+
+        game.player.current_hp = game.player.max_hp + 5;
+        assert_eq!("[xxxx]", format_prompt(&game, "{hp_bar}"));
+        assert_eq!("[xxxx]", plain_bar(4, 12, 10));
+        assert_eq!("[----]", plain_bar(4, -2, 10));
+    }
+
     #[test]
     fn test_bar_slots() {
         // simple case 1:1 between points and slots
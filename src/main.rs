@@ -1,16 +1,11 @@
-use game::Game;
-
-mod character;
-mod datafile;
-mod event;
-mod game;
-mod item;
-mod location;
-mod log;
-mod quest;
-mod randomizer;
-
-use crate::location::Location;
+use rpg_core::location::filesystem;
+use rpg_core::{character, datafile, game, hook, item, log, quest};
+use rpg_core::{Error, Game, Location};
+
+mod script;
+mod shell;
+mod tui;
+
 use clap::{crate_version, AppSettings, Clap};
 
 /// Your filesystem as a dungeon!
@@ -70,9 +65,50 @@ enum Command {
     #[clap(alias = "u", display_order = 3)]
     Use { item: Option<String> },
 
+    /// Learns a skill, once the hero has reached the required level.
+    /// If name is omitted lists the skills.
+    #[clap(alias = "l", display_order = 4)]
+    Learn { skill: Option<String> },
+
+    /// Lists the hero's skills and sets when they are used during battles.
+    #[clap(display_order = 4)]
+    Skills {
+        /// Policy for using skills in battle: never, defensive, balanced or aggressive.
+        #[clap(long)]
+        policy: Option<String>,
+    },
+
+    /// Prints the hero's home directory, where it heals and shops, or moves it.
+    #[clap(display_order = 4)]
+    Home {
+        #[clap(subcommand)]
+        action: Option<HomeAction>,
+    },
+
+    /// Lists the camps, where the hero rests and shops away from home, or
+    /// establishes and removes them at the current location.
+    #[clap(display_order = 4)]
+    Camp {
+        #[clap(subcommand)]
+        action: Option<CampAction>,
+    },
+
+    /// Moves the hero to a warp point, paying a fee according to the distance.
+    /// If name is omitted lists the warp points, found by beating legendary enemies.
+    #[clap(alias = "w", display_order = 1)]
+    Warp { name: Option<String> },
+
+    /// Lists the achievements and when they were unlocked.
+    #[clap(display_order = 4)]
+    Achievements,
+
     /// Prints the quest todo list.
     #[clap(alias = "t", display_order = 4)]
-    Todo,
+    Todo {
+        /// Include locked quests and show the progress of each one.
+        #[clap(long)]
+        all: bool,
+    },
 
     /// Resets the current game.
     Reset {
@@ -85,6 +121,77 @@ enum Command {
     #[clap(name = "pwd")]
     PrintWorkDir,
 
+    /// Prints the hero status with a custom format, meant for shell prompts.
+    /// This doesn't trigger any game event nor change the game data.
+    Prompt {
+        /// Format string with placeholders: {name}, {level}, {hp}, {max_hp}, {hp_bar},
+        /// {mp}, {max_mp}, {mp_bar}, {xp}, {next_xp}, {xp_bar}, {gold}, {location}, {status}.
+        #[clap(long, default_value = "{name}[{level}]{hp_bar}{xp_bar}@{location}")]
+        format: String,
+    },
+
+    /// Prints shell integration code, e.g. `eval "$(rpg-cli init bash)"`.
+    Init {
+        /// The shell to integrate with: bash, zsh or fish.
+        shell: String,
+
+        /// Override cd: none, stop (walk one directory at a time and stop
+        /// at battles) or arrive (reach the destination, then maybe battle).
+        #[clap(long, default_value = "none")]
+        cd: String,
+
+        /// Comma separated commands that require winning a battle to run, e.g. rm,mv.
+        #[clap(long)]
+        battle_on: Option<String>,
+
+        /// Look for chests when running ls without arguments.
+        #[clap(long)]
+        ls: bool,
+
+        /// Show the hero status in the prompt.
+        #[clap(long)]
+        prompt: bool,
+    },
+
+    /// Runs battles against the enemies at some distance from home and prints the
+    /// win rate, average turns and HP left per enemy class, to check the game balance.
+    /// This doesn't change the game data.
+    Simulate {
+        /// Level of the hero, who wears the equipment of that level and carries potions.
+        #[clap(long, default_value = "1")]
+        level: i32,
+
+        /// Distance from home where the enemies are found.
+        #[clap(long, default_value = "1")]
+        distance: i32,
+
+        /// Number of battles to run.
+        #[clap(long, default_value = "100")]
+        runs: i32,
+
+        /// Only fight enemies of the given class.
+        #[clap(long)]
+        class: Option<String>,
+
+        /// Seed for the randomness, the same seed gives the same results.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+    },
+
+    /// Runs the commands of a file, one per line, against a new game with seeded
+    /// randomness and prints the transcript. This doesn't change the game data.
+    Script {
+        /// File with one command per line, e.g. `cd dev` or `buy potion`.
+        file: String,
+
+        /// Seed for the randomness, the same seed gives the same transcript.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+    },
+
+    /// Opens a full screen interface to browse directories, shop and follow quests.
+    Tui,
+
     /// Potentially initiates a battle in the hero's current location.
     Battle {
         /// Attempt to avoid battles by running away.
@@ -97,11 +204,29 @@ enum Command {
     },
 }
 
-fn main() {
-    let mut exit_code = 0;
+#[derive(Clap)]
+enum HomeAction {
+    /// Moves the home, along with the hero, to the given directory.
+    /// The hero needs to be at home.
+    Set { path: String },
 
+    /// Moves the home back to the user's home directory.
+    Reset,
+}
+
+#[derive(Clap)]
+enum CampAction {
+    /// Establishes a camp at the current location, for 1000g.
+    Set,
+
+    /// Removes the camp at the current location.
+    Remove,
+}
+
+fn main() {
     let opts: Opts = Opts::parse();
     log::init(opts.quiet, opts.plain);
+    filesystem::set(real_filesystem());
 
     // reset --hard is a special case, it needs to work when we
     // fail to deserialize the game data -- e.g. on backward
@@ -110,101 +235,296 @@ fn main() {
         datafile::remove();
     }
 
-    let mut game = datafile::load().unwrap_or_else(|_| Game::new());
+    // init only prints the shell integration code, so it shouldn't
+    // depend on the game data either
+    if let Some(Command::Init {
+        shell,
+        cd,
+        battle_on,
+        ls,
+        prompt,
+    }) = &opts.cmd
+    {
+        if let Err(err) = init(shell, cd, battle_on.clone(), *ls, *prompt) {
+            exit(err);
+        }
+        return;
+    }
+
+    // the prompt is printed on every shell prompt, so it skips the
+    // event handlers and never writes the game data. It prints nothing
+    // if the data can't be read, rather than an error on every prompt
+    if let Some(Command::Prompt { format }) = &opts.cmd {
+        if let Ok(game) = datafile::load() {
+            let game = game.unwrap_or_else(Game::new);
+            println!("{}", log::format_prompt(&game, format));
+        }
+        return;
+    }
+
+    // scripts play a new game, so the saved one is not loaded and
+    // its enemy classes and quests are not used in the transcript
+    if let Some(Command::Script { file, seed }) = &opts.cmd {
+        if let Err(err) = script::run_file(file, *seed) {
+            exit(err);
+        }
+        return;
+    }
+
+    let mut game = match datafile::load() {
+        Ok(game) => game.unwrap_or_else(Game::new),
+        Err(err) => exit(err),
+    };
+
+    game.achievements = datafile::load_achievements();
+    let snapshot = datafile::Snapshot::of(&game);
+    game.events.subscribe(Box::new(log::Handler));
+    game.events.subscribe(Box::new(hook::Handler::load()));
+
+    let result = run(&mut game, opts.cmd.unwrap_or(Command::Stat));
+    if let Err(Error::HeroDied) = result {
+        game.reset();
+    }
+
+    if let Err(err) = datafile::save(&game, &snapshot) {
+        exit(Error::from(err));
+    }
+    if let Err(err) = result {
+        exit(err);
+    }
+}
+
+/// Print the error message, if any, and exit with the error's code.
+fn exit(err: Error) -> ! {
+    // the death is already displayed by the battle log
+    if err != Error::HeroDied {
+        eprintln!("{}", err);
+    }
+    std::process::exit(err.exit_code());
+}
+
+fn run(game: &mut Game, cmd: Command) -> Result<(), Error> {
+    game.quests
+        .refresh_bounties(game.player.level, quest::today());
 
-    match opts.cmd.unwrap_or(Command::Stat) {
-        Command::Stat => log::status(&game),
+    match cmd {
+        Command::Stat => log::status(game),
         Command::ChangeDir {
             destination,
             run,
             bribe,
             force,
-        } => {
-            exit_code = change_dir(&mut game, &destination, run, bribe, force);
-        }
-        Command::Inspect => {
-            game.inspect();
-        }
-        Command::Battle { run, bribe } => {
-            exit_code = battle(&mut game, run, bribe);
-        }
+        } => change_dir(game, &destination, run, bribe, force)?,
+        Command::Inspect => game.inspect()?,
+        Command::Battle { run, bribe } => battle(game, run, bribe)?,
         Command::PrintWorkDir => println!("{}", game.location.path_string()),
         Command::Reset { .. } => game.reset(),
-        Command::Buy { item } => shop(&mut game, &item),
-        Command::Use { item } => use_item(&mut game, &item),
-        Command::Todo => {
-            let (todo, done) = game.quests.list(&game);
-            log::quest_list(&todo, &done);
+        Command::Buy { item } => shop(game, &item)?,
+        Command::Use { item } => use_item(game, &item)?,
+        Command::Learn { skill } => learn(game, &skill)?,
+        Command::Skills { policy } => skills(game, &policy)?,
+        Command::Prompt { format } => println!("{}", log::format_prompt(game, &format)),
+        Command::Init {
+            shell,
+            cd,
+            battle_on,
+            ls,
+            prompt,
+        } => init(&shell, &cd, battle_on, ls, prompt)?,
+        Command::Simulate {
+            level,
+            distance,
+            runs,
+            class,
+            seed,
+        } => simulate(level, distance, runs, class, seed)?,
+        Command::Script { file, seed } => script::run_file(&file, seed)?,
+        Command::Tui => tui::run(game)?,
+        Command::Warp { name: None } => log::warp_list(game),
+        Command::Warp { name: Some(name) } => game.warp(&name)?,
+        Command::Home { action } => home(game, action)?,
+        Command::Camp { action: None } => log::camp_list(game),
+        Command::Camp {
+            action: Some(CampAction::Set),
+        } => game.set_camp()?,
+        Command::Camp {
+            action: Some(CampAction::Remove),
+        } => game.remove_camp()?,
+        Command::Achievements => log::achievements(&game.achievements.list()),
+        Command::Todo { all } => {
+            let (todo, done) = game.quests.list(game, all);
+            log::quest_list(&todo, &done, all);
         }
     }
-
-    datafile::save(&game).unwrap();
-    std::process::exit(exit_code);
+    Ok(())
 }
 
 /// Attempt to move the hero to the supplied location, possibly engaging
 /// in combat along the way.
-fn change_dir(game: &mut Game, dest: &str, run: bool, bribe: bool, force: bool) -> i32 {
-    if let Ok(dest) = Location::from(&dest) {
-        if force {
-            game.location = dest;
-        } else if let Err(character::Dead) = game.go_to(&dest, run, bribe) {
-            game.reset();
-            return 1;
-        }
+fn change_dir(
+    game: &mut Game,
+    dest: &str,
+    run: bool,
+    bribe: bool,
+    force: bool,
+) -> Result<(), Error> {
+    let dest = Location::from(&dest).map_err(|_| Error::NoSuchPath)?;
+    if force {
+        game.location = dest;
     } else {
-        println!("No such file or directory");
-        return 1;
+        game.go_to(&dest, run, bribe)?;
     }
-    0
+    Ok(())
 }
 
 /// Potentially run a battle at the current location, independently from
 /// the hero's movement.
-fn battle(game: &mut Game, run: bool, bribe: bool) -> i32 {
-    let mut exit_code = 0;
+fn battle(game: &mut Game, run: bool, bribe: bool) -> Result<(), Error> {
+    if game.is_safe() {
+        return Ok(());
+    }
     if let Some(mut enemy) = game.maybe_spawn_enemy() {
-        if let Err(character::Dead) = game.maybe_battle(&mut enemy, run, bribe) {
-            game.reset();
-            exit_code = 1;
+        game.maybe_battle(&mut enemy, run, bribe)?;
+    }
+    Ok(())
+}
+
+/// Run the battle simulation and print the results for each enemy class.
+fn simulate(
+    level: i32,
+    distance: i32,
+    runs: i32,
+    class: Option<String>,
+    seed: u64,
+) -> Result<(), Error> {
+    if level < 1 || distance < 1 || runs < 1 {
+        return Err(Error::InvalidInput(String::from(
+            "Level, distance and runs must be positive.",
+        )));
+    }
+    let reports = game::simulation::run(level, distance, runs, class.as_deref(), seed)?;
+    log::simulation(&reports);
+    Ok(())
+}
+
+/// Print the home directory, or move it along with the hero.
+fn home(game: &mut Game, action: Option<HomeAction>) -> Result<(), Error> {
+    let home = match action {
+        None => {
+            println!("{}", Location::home().path_string());
+            return Ok(());
+        }
+        Some(HomeAction::Set { path }) => {
+            Some(Location::from(&path).map_err(|_| Error::NoSuchPath)?)
         }
+        Some(HomeAction::Reset) => None,
+    };
+    if !game.location.is_home() {
+        return Err(Error::InvalidInput(String::from(
+            "The hero needs to be at home to move it.",
+        )));
     }
-    exit_code
+
+    datafile::write_home(home.as_ref())?;
+    filesystem::set(real_filesystem());
+    game.visit_home();
+    Ok(())
+}
+
+/// The filesystem of the machine, with the home set by `rpg home set`, if any.
+fn real_filesystem() -> filesystem::Real {
+    datafile::read_home().map_or_else(filesystem::Real::default, filesystem::Real::new)
+}
+
+/// Print the shell integration code for the given shell and options.
+fn init(
+    shell: &str,
+    cd: &str,
+    battle_on: Option<String>,
+    ls: bool,
+    prompt: bool,
+) -> Result<(), Error> {
+    let shell = shell::Shell::from_name(shell).ok_or_else(|| {
+        Error::InvalidInput(String::from("Unknown shell, expected bash, zsh or fish."))
+    })?;
+    let cd = shell::CdMode::from_name(cd).ok_or_else(|| {
+        Error::InvalidInput(String::from(
+            "Unknown cd mode, expected none, stop or arrive.",
+        ))
+    })?;
+    let battle_on = shell::parse_commands(&battle_on.unwrap_or_default())
+        .map_err(|name| Error::InvalidInput(format!("Invalid command name: {}", name)))?;
+
+    let options = shell::Options {
+        cd,
+        battle_on,
+        ls,
+        prompt,
+    };
+    print!("{}", shell::init(shell, &options));
+    Ok(())
 }
 
 /// Buy an item from the shop or list the available items if no item name is provided.
-/// Shopping is only allowed when the player is at the home directory.
-fn shop(game: &mut Game, item_name: &Option<String>) {
-    if game.location.is_home() {
-        if let Some(item_name) = item_name {
-            let item_name = sanitize(item_name);
-            match item::shop::buy(game, &item_name) {
-                Err(item::shop::Error::NotEnoughGold) => {
-                    println!("Not enough gold.")
-                }
-                Err(item::shop::Error::ItemNotAvailable) => {
-                    println!("Item not available.")
-                }
-                Ok(()) => {}
-            }
-        } else {
-            item::shop::list(game);
-        }
+/// Shopping is only allowed when the player is at home or at a camp.
+fn shop(game: &mut Game, item_name: &Option<String>) -> Result<(), Error> {
+    if !game.is_safe() {
+        return Err(Error::InvalidInput(String::from(
+            "Shop is only allowed at home or at camps.",
+        )));
+    }
+
+    if let Some(item_name) = item_name {
+        let item_name = sanitize(item_name);
+        item::shop::buy(game, &item_name)?;
     } else {
-        println!("Shop is only allowed at home.")
+        item::shop::list(game);
     }
+    Ok(())
 }
 
 /// Use an item from the inventory or list the inventory contents if no item name is provided.
-fn use_item(game: &mut Game, item_name: &Option<String>) {
+fn use_item(game: &mut Game, item_name: &Option<String>) -> Result<(), Error> {
     if let Some(item_name) = item_name {
         let item_name = sanitize(item_name);
-        if let Err(game::ItemNotFound) = game.use_item(&item_name) {
-            println!("Item not found.");
+        game.use_item(&item_name)?;
+    } else {
+        println!("{}", log::format_inventory(game));
+    }
+    Ok(())
+}
+
+/// Learn a skill or list the skills if no skill name is provided.
+fn learn(game: &mut Game, skill_name: &Option<String>) -> Result<(), Error> {
+    if let Some(skill_name) = skill_name {
+        let skill = character::skill::Skill::from_name(&skill_name.to_lowercase())
+            .ok_or_else(|| Error::InvalidInput(String::from("Skill not found.")))?;
+        match game.player.learn(skill) {
+            Err(character::skill::Error::Locked) => {
+                return Err(Error::InvalidInput(format!(
+                    "Skill unlocks at level {}.",
+                    skill.unlock_level()
+                )))
+            }
+            Err(character::skill::Error::AlreadyLearned) => {
+                return Err(Error::InvalidInput(String::from("Skill already learned.")))
+            }
+            Ok(()) => {}
         }
     } else {
-        println!("{}", log::format_inventory(&game));
+        log::skill_list(game);
+    }
+    Ok(())
+}
+
+/// Set the skill policy, if provided, and list the skills.
+fn skills(game: &mut Game, policy: &Option<String>) -> Result<(), Error> {
+    if let Some(policy) = policy {
+        game.skill_policy = character::skill::SkillPolicy::from_name(&policy.to_lowercase())
+            .ok_or_else(|| Error::InvalidInput(String::from("Unknown policy.")))?;
     }
+    log::skill_list(game);
+    Ok(())
 }
 
 /// Return a clean version of an item/equipment name, including aliases
@@ -1,11 +1,11 @@
 use std::collections::HashSet;
 
-use super::Quest;
+use super::{Progress, Quest};
 use crate::character::class::Class;
 use crate::event::Event;
 use serde::{Deserialize, Serialize};
 
-pub fn of_class(classes: &[Class], description: &str) -> Box<dyn Quest> {
+pub fn of_class(classes: &[&Class], description: &str) -> Box<dyn Quest> {
     let to_beat = classes.iter().map(|c| c.name.to_string()).collect();
     Box::new(BeatEnemyClass {
         to_beat,
@@ -15,7 +15,18 @@ pub fn of_class(classes: &[Class], description: &str) -> Box<dyn Quest> {
 }
 
 pub fn at_distance(distance: i32) -> Box<dyn Quest> {
-    Box::new(BeatEnemyDistance { distance })
+    Box::new(BeatEnemyDistance {
+        distance,
+        farthest: 0,
+    })
+}
+
+pub fn count(class: Option<String>, total: i32) -> Box<dyn Quest> {
+    Box::new(BeatEnemyCount {
+        class,
+        total,
+        beaten: 0,
+    })
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -28,8 +39,7 @@ pub struct BeatEnemyClass {
 #[typetag::serde]
 impl Quest for BeatEnemyClass {
     fn description(&self) -> String {
-        let already_beat = self.total - self.to_beat.len();
-        format!("{} {}/{}", self.description, already_beat, self.total)
+        self.description.clone()
     }
 
     fn handle(&mut self, event: &Event) -> bool {
@@ -38,11 +48,23 @@ impl Quest for BeatEnemyClass {
         }
         self.to_beat.is_empty()
     }
+
+    fn progress(&self) -> Progress {
+        let already_beat = self.total - self.to_beat.len();
+        let mut remaining: Vec<String> = self.to_beat.iter().cloned().collect();
+        remaining.sort();
+        Progress {
+            remaining,
+            ..Progress::new(already_beat as i32, self.total as i32)
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BeatEnemyDistance {
     distance: i32,
+    #[serde(default)]
+    farthest: i32,
 }
 
 #[typetag::serde]
@@ -53,10 +75,44 @@ impl Quest for BeatEnemyDistance {
 
     fn handle(&mut self, event: &Event) -> bool {
         if let Event::BattleWon { location, .. } = event {
-            if location.distance_from_home().len() >= self.distance {
+            let distance = location.distance_from_home().len();
+            self.farthest = std::cmp::max(self.farthest, distance);
+            if distance >= self.distance {
                 return true;
             }
         }
         false
     }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.farthest, self.distance)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BeatEnemyCount {
+    class: Option<String>,
+    total: i32,
+    beaten: i32,
+}
+
+#[typetag::serde]
+impl Quest for BeatEnemyCount {
+    fn description(&self) -> String {
+        let class = self.class.as_deref().unwrap_or("enemies");
+        format!("defeat {} {}", self.total, class)
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        if let Event::BattleWon { enemy, .. } = event {
+            if self.class.is_none() || self.class == Some(enemy.name()) {
+                self.beaten += 1;
+            }
+        }
+        self.beaten >= self.total
+    }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.beaten, self.total)
+    }
 }
@@ -0,0 +1,105 @@
+use super::items::Collect;
+use super::pack::Goal;
+use super::reward::Reward;
+use super::Quest;
+use crate::character::class::{Category, Class};
+use crate::randomizer::{random, with_rng, Randomizer};
+use rand::prelude::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a bounty lasts before it expires.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Daily,
+    Weekly,
+}
+
+impl Period {
+    /// The number of the day or week of the given day since the epoch,
+    /// used to tell when the bounties of the period expire.
+    pub fn key(&self, day: i64) -> i64 {
+        match self {
+            Period::Daily => day,
+            // the epoch was a thursday, shift so weeks start on monday
+            Period::Weekly => (day + 3) / 7,
+        }
+    }
+}
+
+/// The number of the current day since the epoch.
+pub fn today() -> i64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    (secs / 86400) as i64
+}
+
+/// A repeatable quest that is only available during the day or week
+/// it was generated in.
+#[derive(Serialize, Deserialize)]
+pub struct Bounty {
+    pub period: Period,
+    pub key: i64,
+    pub reward: Reward,
+    pub quest: Box<dyn Quest>,
+    pub completed: bool,
+}
+
+impl Bounty {
+    /// Generate a random bounty for the period of the given day, with a reward
+    /// according to the hero level.
+    pub fn generate(period: Period, level: i32, day: i64) -> Self {
+        // any class counts if the category was left empty by the user classes
+        let class_name = |category| {
+            let classes = Class::of_category(category);
+            with_rng(|rng| classes.choose(rng).map(|c| c.name.clone()))
+        };
+
+        let (quest, gold): (Box<dyn Quest>, i32) = match (period, random().range(3)) {
+            (Period::Daily, 0) => (
+                Goal::Defeat {
+                    class: class_name(Category::Common),
+                    count: 3,
+                }
+                .quest(),
+                level * 50,
+            ),
+            (Period::Daily, 1) => (
+                Goal::Defeat {
+                    class: None,
+                    count: 10,
+                }
+                .quest(),
+                level * 80,
+            ),
+            // buying the items would make the bounty trivial
+            (Period::Daily, _) => (Box::new(Collect::found("potion", 2)), level * 40),
+            (Period::Weekly, 0) => (Goal::FindChests(3).quest(), level * 300),
+            (Period::Weekly, 1) => (
+                Goal::Defeat {
+                    class: class_name(Category::Rare),
+                    count: 3,
+                }
+                .quest(),
+                level * 400,
+            ),
+            (Period::Weekly, _) => (
+                Goal::BeatAtDistance(std::cmp::min(level + 3, 12)).quest(),
+                level * 300,
+            ),
+        };
+
+        Self {
+            period,
+            key: period.key(day),
+            reward: Reward::Gold(gold),
+            quest,
+            completed: false,
+        }
+    }
+
+    pub fn is_expired(&self, day: i64) -> bool {
+        self.key != self.period.key(day)
+    }
+}
@@ -0,0 +1,101 @@
+use super::{Progress, Quest};
+use crate::event::Event;
+use crate::location::Location;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Visit {
+    path: String,
+}
+
+impl Visit {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+        }
+    }
+}
+
+#[typetag::serde]
+impl Quest for Visit {
+    fn description(&self) -> String {
+        format!("visit {}", self.path)
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        if let Event::Visit { location } = event {
+            // the path is resolved on each visit since it may not exist yet
+            // when the quest is created
+            if let Ok(target) = Location::from(&self.path) {
+                return target == *location;
+            }
+        }
+        false
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReachDistance {
+    distance: i32,
+    #[serde(default)]
+    farthest: i32,
+}
+
+impl ReachDistance {
+    pub fn new(distance: i32) -> Self {
+        Self {
+            distance,
+            farthest: 0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Quest for ReachDistance {
+    fn description(&self) -> String {
+        format!("go {} steps away from home", self.distance)
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        if let Event::Visit { location } = event {
+            let distance = location.distance_from_home().len();
+            self.farthest = std::cmp::max(self.farthest, distance);
+            return distance >= self.distance;
+        }
+        false
+    }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.farthest, self.distance)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FindChests {
+    total: i32,
+    found: i32,
+}
+
+impl FindChests {
+    pub fn new(total: i32) -> Self {
+        Self { total, found: 0 }
+    }
+}
+
+#[typetag::serde]
+impl Quest for FindChests {
+    fn description(&self) -> String {
+        format!("find {} chests", self.total)
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        if let Event::ChestFound { .. } = event {
+            self.found += 1;
+        }
+        self.found >= self.total
+    }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.found, self.total)
+    }
+}
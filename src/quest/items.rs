@@ -0,0 +1,86 @@
+use super::{Progress, Quest};
+use crate::event::Event;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Collect {
+    item: String,
+    total: i32,
+    collected: i32,
+    /// Leave out the items bought at the shop.
+    #[serde(default)]
+    found_only: bool,
+}
+
+impl Collect {
+    pub fn new(item: &str, total: i32) -> Self {
+        Self {
+            item: item.to_string(),
+            total,
+            collected: 0,
+            found_only: false,
+        }
+    }
+
+    /// Collect items found in chests, tombstones or enemy drops.
+    pub fn found(item: &str, total: i32) -> Self {
+        Self {
+            found_only: true,
+            ..Self::new(item, total)
+        }
+    }
+}
+
+#[typetag::serde]
+impl Quest for Collect {
+    fn description(&self) -> String {
+        format!("collect {} {}", self.total, self.item)
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        let found: &[String] = match event {
+            Event::ChestFound { items, .. }
+            | Event::TombstoneFound { items, .. }
+            | Event::BattleWon { items, .. } => items,
+            Event::ItemBought { item, .. } if !self.found_only => std::slice::from_ref(item),
+            _ => &[],
+        };
+        self.collected += found.iter().filter(|item| **item == self.item).count() as i32;
+        self.collected >= self.total
+    }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.collected, self.total)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpendGold {
+    total: i32,
+    spent: i32,
+}
+
+impl SpendGold {
+    pub fn new(total: i32) -> Self {
+        Self { total, spent: 0 }
+    }
+}
+
+#[typetag::serde]
+impl Quest for SpendGold {
+    fn description(&self) -> String {
+        format!("spend {}g", self.total)
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        match event {
+            Event::ItemBought { cost, .. } | Event::Bribe { cost } => self.spent += cost,
+            _ => {}
+        }
+        self.spent >= self.total
+    }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.spent, self.total)
+    }
+}
@@ -1,25 +1,54 @@
-use crate::character;
 use crate::event;
 use crate::game;
-use crate::log;
 use core::fmt;
 use serde::{Deserialize, Serialize};
 
 mod beat_enemy;
+mod bounty;
+mod explore;
+mod items;
+mod pack;
+pub mod reward;
 mod tutorial;
 
-pub fn handle(game: &mut game::Game, event: &event::Event) {
+pub use bounty::today;
+use bounty::{Bounty, Period};
+use reward::Reward;
+
+/// Updates the quests progress and applies the rewards of the completed ones.
+pub struct Handler;
+
+impl event::Subscriber for Handler {
+    fn handle(&mut self, game: &mut game::Game, event: &event::Event) {
+        handle(game, event);
+    }
+}
+
+fn handle(game: &mut game::Game, event: &event::Event) {
     // it would be preferable to have quests decoupled from the game struct
     // but that makes event handling much more complicated
-    game.gold += game.quests.handle(event);
+    for (description, reward) in game.quests.handle(event) {
+        let reward = reward.apply(game).unwrap_or(Reward::Many(Vec::new()));
+        event::Event::emit(
+            game,
+            event::Event::QuestCompleted {
+                description,
+                reward,
+            },
+        );
+    }
 }
 
 /// Keeps a TODO list of quests for the game.
 /// Each quest is unlocked at a certain level and has completion reward.
+/// Besides the one-off quests, a daily and a weekly bounty are kept,
+/// which are replaced when their period expires.
 #[derive(Serialize, Deserialize, Default)]
 pub struct QuestList {
-    todo: Vec<(i32, i32, Box<dyn Quest>)>,
+    todo: Vec<(i32, Reward, Box<dyn Quest>)>,
     done: Vec<String>,
+    #[serde(default)]
+    bounties: Vec<Bounty>,
 }
 
 impl QuestList {
@@ -27,84 +56,114 @@ impl QuestList {
         let mut quests = Self {
             todo: Vec::new(),
             done: Vec::new(),
+            bounties: Vec::new(),
         };
 
         quests.setup();
         quests
     }
 
-    /// Load the quests for a new game
+    /// Load the quests for a new game from the quest packs.
     fn setup(&mut self) {
-        self.todo.push((1, 100, Box::new(tutorial::WinBattle)));
-        self.todo.push((1, 100, Box::new(tutorial::BuySword)));
-        self.todo.push((1, 100, Box::new(tutorial::UsePotion)));
-        self.todo
-            .push((1, 100, Box::new(tutorial::ReachLevel::new(2))));
-
-        self.todo.push((2, 200, Box::new(tutorial::FindChest)));
-        self.todo
-            .push((2, 500, Box::new(tutorial::ReachLevel::new(5))));
-        self.todo.push((
-            2,
-            1000,
-            beat_enemy::of_class(&character::class::COMMON, "beat all common creatures"),
-        ));
-
-        self.todo.push((5, 200, Box::new(tutorial::VisitTomb)));
-        self.todo
-            .push((5, 1000, Box::new(tutorial::ReachLevel::new(10))));
-        self.todo.push((
-            5,
-            5000,
-            beat_enemy::of_class(&character::class::RARE, "beat all rare creatures"),
-        ));
-        self.todo.push((5, 1000, beat_enemy::at_distance(10)));
-
-        self.todo.push((
-            10,
-            10000,
-            beat_enemy::of_class(&character::class::LEGENDARY, "beat all common creatures"),
-        ));
+        for definition in pack::load() {
+            self.todo
+                .push((definition.level, definition.reward, definition.goal.quest()));
+        }
     }
 
     /// Pass the event to each of the quests, moving the completed ones to DONE.
-    /// The total gold reward is returned.
-    fn handle(&mut self, event: &event::Event) -> i32 {
+    /// The descriptions and rewards of the completed quests are returned.
+    fn handle(&mut self, event: &event::Event) -> Vec<(String, Reward)> {
         let mut still_todo = Vec::new();
-        let mut total_reward = 0;
+        let mut rewards = Vec::new();
 
         for (unlock_at, reward, mut quest) in self.todo.drain(..) {
             let is_done = quest.handle(event);
 
             if is_done {
-                total_reward += reward;
-                log::quest_done(reward);
-
                 // the done is stored from newer to older
-                self.done.insert(0, quest.description().to_string());
+                self.done.insert(0, quest.description());
+                rewards.push((quest.description(), reward));
             } else {
                 still_todo.push((unlock_at, reward, quest));
             }
         }
 
         self.todo = still_todo;
-        total_reward
+
+        for bounty in self.bounties.iter_mut().filter(|b| !b.completed) {
+            if bounty.quest.handle(event) {
+                bounty.completed = true;
+                rewards.push((bounty.quest.description(), bounty.reward.clone()));
+            }
+        }
+
+        rewards
     }
 
-    pub fn list(&self, game: &game::Game) -> (Vec<String>, Vec<String>) {
-        let todo = self
-            .todo
+    /// Drop the bounties of past days or weeks and generate new ones for
+    /// the period of the given day if missing. Completed bounties are kept
+    /// until they expire, so only one is offered per period.
+    /// This is meant to be called once per command, e.g. with `today()`.
+    pub fn refresh_bounties(&mut self, level: i32, day: i64) {
+        self.bounties.retain(|b| !b.is_expired(day));
+        for period in &[Period::Daily, Period::Weekly] {
+            if !self.bounties.iter().any(|b| b.period == *period) {
+                self.bounties.push(Bounty::generate(*period, level, day));
+            }
+        }
+    }
+
+    /// Return the pending and the completed quests. Quests not yet
+    /// unlocked at the current level are only included if `all` is set.
+    pub fn list(&self, game: &game::Game, all: bool) -> (Vec<Entry>, Vec<String>) {
+        let bounty_description = |b: &Bounty| {
+            let period = match b.period {
+                Period::Daily => "daily",
+                Period::Weekly => "weekly",
+            };
+            format!("{} ({})", b.quest.description(), period)
+        };
+
+        let mut todo: Vec<Entry> = self
+            .bounties
             .iter()
-            .filter(|(level, _, _)| &game.player.level >= level)
-            .map(|(_, _, q)| q.description())
+            .filter(|b| !b.completed)
+            .map(|b| Entry {
+                description: bounty_description(b),
+                progress: b.quest.progress(),
+                reward: b.reward.clone(),
+                unlock_at: 1,
+                locked: false,
+            })
             .collect();
+        todo.extend(
+            self.todo
+                .iter()
+                .map(|(level, reward, q)| Entry {
+                    description: q.description(),
+                    progress: q.progress(),
+                    reward: reward.clone(),
+                    unlock_at: *level,
+                    locked: game.player.level < *level,
+                })
+                .filter(|entry| all || !entry.locked),
+        );
 
-        (todo, self.done.clone())
+        let mut done: Vec<String> = self
+            .bounties
+            .iter()
+            .filter(|b| b.completed)
+            .map(bounty_description)
+            .collect();
+        done.extend(self.done.iter().cloned());
+
+        (todo, done)
     }
 }
 
 /// A task that is assigned to the player when certain conditions are met.
-/// New quests should implement this trait and be added to QuestList.setup method.
+/// New quests should implement this trait and be exposed as a Goal in the pack module.
 #[typetag::serde(tag = "type")]
 pub trait Quest {
     /// What to show in the TODO quests list
@@ -113,6 +172,40 @@ pub trait Quest {
     /// Update the quest progress based on the given event and
     /// return whether the quest was finished.
     fn handle(&mut self, event: &event::Event) -> bool;
+
+    /// How far along the quest is. Quests completed by a single
+    /// event don't need to override this.
+    fn progress(&self) -> Progress {
+        Progress::new(0, 1)
+    }
+}
+
+/// The structured progress of a quest, e.g. 2 out of 5 enemies beaten,
+/// with the names of the pending items when the quest tracks them.
+#[derive(Debug, PartialEq)]
+pub struct Progress {
+    pub current: i32,
+    pub target: i32,
+    pub remaining: Vec<String>,
+}
+
+impl Progress {
+    pub fn new(current: i32, target: i32) -> Self {
+        Self {
+            current: std::cmp::min(current, target),
+            target,
+            remaining: Vec::new(),
+        }
+    }
+}
+
+/// What the TODO list shows of a pending quest.
+pub struct Entry {
+    pub description: String,
+    pub progress: Progress,
+    pub reward: Reward,
+    pub unlock_at: i32,
+    pub locked: bool,
 }
 
 impl fmt::Display for dyn Quest {
@@ -128,7 +221,7 @@ mod tests {
     #[test]
     fn test_quest_completed() {
         let mut game = game::Game::new();
-        let fake_enemy = character::Character::player();
+        let fake_enemy = crate::character::Character::player();
 
         let initial_quests = game.quests.todo.len();
         assert!(initial_quests > 0);
@@ -139,11 +232,12 @@ mod tests {
         event::Event::emit(
             &mut game,
             event::Event::BattleWon {
-                enemy: &fake_enemy,
+                enemy: fake_enemy,
                 location,
                 xp: 100,
                 levels_up: 0,
                 gold: 100,
+                items: Vec::new(),
             },
         );
         assert_eq!(initial_quests - 1, game.quests.todo.len());
@@ -158,4 +252,86 @@ mod tests {
         assert_eq!(initial_quests - 1, game.quests.todo.len());
         assert_eq!(1, game.quests.done.len());
     }
+
+    #[test]
+    fn test_collect() {
+        let mut quest = items::Collect::new("potion", 4);
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+
+        let chest = event::Event::ChestFound {
+            items: names(&["potion", "remedy"]),
+            gold: 0,
+        };
+        assert!(!quest.handle(&chest));
+        let tombstone = event::Event::TombstoneFound {
+            items: names(&["potion", "potion"]),
+            gold: 0,
+        };
+        assert!(!quest.handle(&tombstone));
+        let bought = |item: &str| event::Event::ItemBought {
+            item: item.to_string(),
+            cost: 0,
+        };
+        assert!(!quest.handle(&bought("remedy")));
+        assert!(quest.handle(&bought("potion")));
+
+        let mut quest = items::Collect::found("potion", 1);
+        assert!(!quest.handle(&bought("potion")));
+        assert!(quest.handle(&chest));
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut quest = tutorial::ReachLevel::new(5);
+        assert_eq!(Progress::new(1, 5), quest.progress());
+        quest.handle(&event::Event::LevelUp { current: 3 });
+        assert_eq!(Progress::new(3, 5), quest.progress());
+
+        let home = crate::location::Location::home();
+        let parent = home.parent().unwrap();
+        let mut quest = explore::ReachDistance::new(3);
+        quest.handle(&event::Event::Visit { location: parent });
+        quest.handle(&event::Event::Visit { location: home });
+        assert_eq!(Progress::new(1, 3), quest.progress());
+    }
+
+    #[test]
+    fn test_bounties() {
+        // a monday
+        let day = 19648;
+        let mut quests = QuestList::new();
+        assert!(quests.bounties.is_empty());
+
+        quests.refresh_bounties(1, day);
+        assert_eq!(2, quests.bounties.len());
+
+        // completed bounties are not replaced until they expire
+        quests.bounties.iter_mut().for_each(|b| b.completed = true);
+        quests.refresh_bounties(1, day);
+        assert!(quests.bounties.iter().all(|b| b.completed));
+
+        // the next day only the daily bounty is replaced
+        quests.refresh_bounties(1, day + 1);
+        assert_eq!(2, quests.bounties.len());
+        let daily = quests.bounties.iter().find(|b| b.period == Period::Daily);
+        assert!(!daily.unwrap().completed);
+        let weekly = quests.bounties.iter().find(|b| b.period == Period::Weekly);
+        assert!(weekly.unwrap().completed);
+
+        quests.refresh_bounties(1, day + 7);
+        assert!(quests.bounties.iter().all(|b| !b.completed));
+    }
+
+    #[test]
+    fn test_list_locked() {
+        let game = game::Game::new();
+        let (unlocked, _) = game.quests.list(&game, false);
+        let (all, _) = game.quests.list(&game, true);
+
+        assert!(unlocked.iter().all(|e| !e.locked));
+        assert!(all.len() > unlocked.len());
+        assert!(all
+            .iter()
+            .any(|e| e.locked && e.unlock_at > game.player.level));
+    }
 }
@@ -0,0 +1,141 @@
+use super::reward::Reward;
+use super::{beat_enemy, explore, items, tutorial, Quest};
+use crate::character::class::{Category, Class};
+use crate::datafile;
+use serde::Deserialize;
+
+/// A quest entry as declared in a quest pack file.
+/// The quest becomes visible once the hero reaches the given level
+/// and grants the reward when completed.
+#[derive(Deserialize)]
+pub struct Definition {
+    pub level: i32,
+    pub reward: Reward,
+    pub goal: Goal,
+}
+
+/// The building blocks available to declare quests.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    WinBattle,
+    BuySword,
+    UsePotion,
+    FindChest,
+    VisitTomb,
+    ReachLevel(i32),
+    /// Beat every enemy class of a category.
+    BeatAll {
+        category: Category,
+        description: Option<String>,
+    },
+    /// Beat an enemy at least the given steps away from home.
+    BeatAtDistance(i32),
+    /// Beat a number of enemies, of a specific class or any of them.
+    Defeat {
+        class: Option<String>,
+        count: i32,
+    },
+    /// Obtain a number of items with the given name, from chests,
+    /// tombstones, enemy drops or the shop.
+    Collect {
+        item: String,
+        count: i32,
+    },
+    /// Find a number of chests.
+    FindChests(i32),
+    /// Move the hero to the given path.
+    Visit(String),
+    /// Move the hero at least the given steps away from home.
+    ReachDistance(i32),
+    /// Spend an amount of gold on items and bribes.
+    SpendGold(i32),
+}
+
+impl Goal {
+    /// Build the quest that tracks this goal.
+    pub fn quest(self) -> Box<dyn Quest> {
+        match self {
+            Goal::WinBattle => Box::new(tutorial::WinBattle),
+            Goal::BuySword => Box::new(tutorial::BuySword),
+            Goal::UsePotion => Box::new(tutorial::UsePotion),
+            Goal::FindChest => Box::new(tutorial::FindChest),
+            Goal::VisitTomb => Box::new(tutorial::VisitTomb),
+            Goal::ReachLevel(level) => Box::new(tutorial::ReachLevel::new(level)),
+            Goal::BeatAll {
+                category,
+                description,
+            } => {
+                let description = description
+                    .unwrap_or_else(|| format!("beat all {:?} creatures", category).to_lowercase());
+                beat_enemy::of_class(&Class::of_category(category), &description)
+            }
+            Goal::BeatAtDistance(distance) => beat_enemy::at_distance(distance),
+            Goal::Defeat { class, count } => beat_enemy::count(class, count),
+            Goal::Collect { item, count } => Box::new(items::Collect::new(&item, count)),
+            Goal::FindChests(count) => Box::new(explore::FindChests::new(count)),
+            Goal::Visit(path) => Box::new(explore::Visit::new(&path)),
+            Goal::ReachDistance(distance) => Box::new(explore::ReachDistance::new(distance)),
+            Goal::SpendGold(amount) => Box::new(items::SpendGold::new(amount)),
+        }
+    }
+}
+
+const DEFAULT_PACK: &str = include_str!("default.json");
+
+/// Load the quest definitions from the bundled pack followed by the
+/// ones from the packs in the user data directory.
+pub fn load() -> Vec<Definition> {
+    let mut definitions: Vec<Definition> = serde_json::from_str(DEFAULT_PACK).unwrap();
+
+    for (name, data) in datafile::read_quest_packs() {
+        match serde_json::from_slice::<Vec<Definition>>(&data) {
+            Ok(pack) => definitions.extend(pack),
+            Err(err) => eprintln!("Ignoring invalid quest pack {}: {}", name, err),
+        }
+    }
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quest::Progress;
+
+    #[test]
+    fn test_parse() {
+        let pack: Vec<Definition> = serde_json::from_str(DEFAULT_PACK).unwrap();
+        assert!(!pack.is_empty());
+
+        let data = r#"[
+            {"level": 1, "reward": 10, "goal": "win_battle"},
+            {"level": 2, "reward": 20, "goal": {"reach_level": 3}},
+            {"level": 3, "reward": 30, "goal": {"defeat": {"class": "wolf", "count": 5}}},
+            {"level": 3, "reward": 30, "goal": {"beat_all": {"category": "Rare"}}},
+            {"level": 4, "reward": 40, "goal": {"collect": {"item": "potion", "count": 3}}},
+            {"level": 5, "reward": 50, "goal": {"visit": "/tmp"}},
+            {"level": 5, "reward": 50, "goal": {"spend_gold": 1000}}
+        ]"#;
+        let pack: Vec<Definition> = serde_json::from_str(data).unwrap();
+        let quests = pack
+            .into_iter()
+            .map(|d| d.goal.quest())
+            .collect::<Vec<Box<dyn Quest>>>();
+        let descriptions = quests
+            .iter()
+            .map(|q| q.description())
+            .collect::<Vec<String>>();
+
+        assert_eq!("win a battle", descriptions[0]);
+        assert_eq!("reach level 3", descriptions[1]);
+        assert_eq!("defeat 5 wolf", descriptions[2]);
+        assert!(descriptions[3].starts_with("beat all rare creatures"));
+        assert_eq!("collect 3 potion", descriptions[4]);
+        assert_eq!("visit /tmp", descriptions[5]);
+        assert_eq!("spend 1000g", descriptions[6]);
+
+        assert_eq!(Progress::new(0, 1), quests[0].progress());
+        assert_eq!(Progress::new(0, 5), quests[2].progress());
+        assert!(!quests[3].progress().remaining.is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::character::skill::Skill;
+use crate::event::Event;
+use crate::game::Game;
+use crate::item::loot::Loot;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What the hero gets for completing a quest.
+/// A plain number is interpreted as gold, to keep quest definitions short.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Reward {
+    Gold(i32),
+    Xp {
+        xp: i32,
+    },
+    Item {
+        item: Loot,
+    },
+    Bonus {
+        bonus: Attribute,
+        amount: i32,
+    },
+    Skill {
+        skill: Skill,
+    },
+    /// Let a locked enemy class start appearing.
+    Unlock {
+        class: String,
+    },
+    Many(Vec<Reward>),
+}
+
+/// Character attributes that can be permanently raised by a reward.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Attribute {
+    Hp,
+    Mp,
+    Strength,
+    Speed,
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Attribute::Hp => "hp",
+            Attribute::Mp => "mp",
+            Attribute::Strength => "strength",
+            Attribute::Speed => "speed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Reward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reward::Gold(gold) => write!(f, "+{}g", gold),
+            Reward::Xp { xp } => write!(f, "+{}xp", xp),
+            Reward::Item { item } => write!(f, "+{}", item),
+            Reward::Bonus { bonus, amount } => write!(f, "+{}{}", amount, bonus),
+            Reward::Skill { skill } => write!(f, "+{}", skill),
+            Reward::Unlock { class } => write!(f, "+{} unlocked", class),
+            Reward::Many(rewards) => {
+                let rewards = rewards.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", rewards.join(" "))
+            }
+        }
+    }
+}
+
+impl Reward {
+    /// Grant the reward to the hero. Return what was actually granted,
+    /// e.g. equipment is left out if it's not better than the current one.
+    pub fn apply(&self, game: &mut Game) -> Option<Reward> {
+        match self {
+            Reward::Gold(gold) => game.gold += gold,
+            Reward::Xp { xp } => {
+                let levels_up = game.player.add_experience(*xp);
+                if levels_up > 0 {
+                    Event::emit(
+                        game,
+                        Event::LevelUp {
+                            current: game.player.level,
+                        },
+                    );
+                }
+            }
+            Reward::Item { item } => {
+                item.add_to(game, game.player.level)?;
+            }
+            Reward::Bonus { bonus, amount } => {
+                let player = &mut game.player;
+                match bonus {
+                    Attribute::Hp => {
+                        player.max_hp += amount;
+                        player.current_hp += amount;
+                    }
+                    Attribute::Mp => {
+                        player.max_mp += amount;
+                        player.current_mp += amount;
+                    }
+                    Attribute::Strength => player.strength += amount,
+                    Attribute::Speed => player.speed += amount,
+                }
+            }
+            Reward::Skill { skill } => {
+                // skills granted by quests skip the level requirement
+                if !game.player.skills.contains(skill) {
+                    game.player.skills.push(*skill);
+                }
+            }
+            Reward::Unlock { class } => {
+                game.unlocked.insert(class.clone());
+            }
+            Reward::Many(rewards) => {
+                let granted = rewards.iter().filter_map(|r| r.apply(game)).collect();
+                return Some(Reward::Many(granted));
+            }
+        }
+        Some(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::equipment::{Equipment, Sword};
+
+    #[test]
+    fn test_apply() {
+        let data = r#"[100, {"xp": 10}, {"item": "Potion"}, {"bonus": "strength", "amount": 2}, {"skill": "Heal"}, {"class": "hydra"}]"#;
+        let reward: Reward = serde_json::from_str(data).unwrap();
+
+        let mut game = Game::new();
+        let strength = game.player.strength;
+        let granted = reward.apply(&mut game).unwrap();
+        assert_eq!(
+            "+100g +10xp +potion +2strength +heal +hydra unlocked",
+            granted.to_string()
+        );
+
+        assert_eq!(100, game.gold);
+        assert_eq!(10, game.player.xp);
+        assert_eq!(1, *game.inventory().get("potion").unwrap());
+        assert_eq!(strength + 2, game.player.strength);
+        assert!(game.player.can_use(Skill::Heal));
+        assert!(game.unlocked.contains("hydra"));
+
+        // equipment that is not an upgrade is not granted
+        let reward = Reward::Many(vec![
+            Reward::Item {
+                item: Loot::FireSword,
+            },
+            Reward::Gold(10),
+        ]);
+        assert_eq!("+fire sword +10g", reward.to_string());
+        game.player.sword = Some(Sword::new(100));
+        assert_eq!("+10g", reward.apply(&mut game).unwrap().to_string());
+    }
+}
@@ -1,4 +1,4 @@
-use super::Quest;
+use super::{Progress, Quest};
 use crate::event::Event;
 use serde::{Deserialize, Serialize};
 
@@ -26,7 +26,7 @@ impl Quest for BuySword {
     }
 
     fn handle(&mut self, event: &Event) -> bool {
-        if let Event::ItemBought { item } = event {
+        if let Event::ItemBought { item, .. } = event {
             if item.contains("sword") {
                 return true;
             }
@@ -57,11 +57,21 @@ impl Quest for UsePotion {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReachLevel {
     target: i32,
+    /// The highest level reached, starting from the hero's first one.
+    #[serde(default = "first_level")]
+    reached: i32,
+}
+
+fn first_level() -> i32 {
+    1
 }
 
 impl ReachLevel {
     pub fn new(target: i32) -> Self {
-        Self { target }
+        Self {
+            target,
+            reached: first_level(),
+        }
     }
 }
 
@@ -73,10 +83,15 @@ impl Quest for ReachLevel {
 
     fn handle(&mut self, event: &Event) -> bool {
         if let Event::LevelUp { current, .. } = event {
+            self.reached = std::cmp::max(self.reached, *current);
             return *current == self.target;
         }
         false
     }
+
+    fn progress(&self) -> Progress {
+        Progress::new(self.reached, self.target)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
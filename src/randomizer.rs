@@ -3,7 +3,9 @@
 use crate::character::StatusEffect;
 use crate::game::battle::AttackType;
 use crate::location;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::cell::RefCell;
 use std::cmp::max;
 
 /// This trait exposes functions to deal with any element of the game that
@@ -13,6 +15,8 @@ use std::cmp::max;
 pub trait Randomizer {
     fn should_enemy_appear(&self, distance: &location::Distance) -> bool;
 
+    fn should_mimic_appear(&self, distance: &location::Distance) -> bool;
+
     fn bribe_succeeds(&self) -> bool;
 
     fn run_away_succeeds(&self, player_level: i32, enemy_level: i32) -> bool;
@@ -28,8 +32,12 @@ pub trait Randomizer {
         receiver_speed: i32,
     ) -> AttackType;
 
+    fn confused_self_attack(&self) -> bool;
+
     fn gold_gained(&self, base: i32) -> i32;
 
+    fn loot_drops(&self, ratio: u32) -> bool;
+
     fn stat_increase(&self, increase: i32) -> i32;
 
     fn range(&self, max: i32) -> i32;
@@ -47,46 +55,67 @@ pub fn random() -> TestRandomizer {
     TestRandomizer {}
 }
 
+thread_local! {
+    /// When set, replaces the thread rng so results can be reproduced.
+    static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Make the randomness of the current thread reproducible with the given
+/// seed, or go back to the thread rng if none is given.
+pub fn seed(seed: Option<u64>) {
+    SEEDED_RNG.with(|seeded| *seeded.borrow_mut() = seed.map(StdRng::seed_from_u64));
+}
+
+/// Call the given function with the seeded rng, if any, or the thread rng.
+pub fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    SEEDED_RNG.with(|seeded| match seeded.borrow_mut().as_mut() {
+        Some(rng) => f(rng),
+        None => f(&mut rand::thread_rng()),
+    })
+}
+
 pub struct DefaultRandomizer;
 
 impl Randomizer for DefaultRandomizer {
     fn should_enemy_appear(&self, distance: &location::Distance) -> bool {
-        let mut rng = rand::thread_rng();
-
-        match distance {
+        with_rng(|rng| match distance {
             location::Distance::Near(_) => rng.gen_ratio(1, 3),
             location::Distance::Mid(_) => rng.gen_ratio(1, 2),
             location::Distance::Far(_) => rng.gen_ratio(2, 3),
-        }
+        })
+    }
+
+    fn should_mimic_appear(&self, distance: &location::Distance) -> bool {
+        with_rng(|rng| match distance {
+            location::Distance::Near(_) => rng.gen_ratio(1, 10),
+            location::Distance::Mid(_) => rng.gen_ratio(1, 5),
+            location::Distance::Far(_) => rng.gen_ratio(1, 3),
+        })
     }
 
     fn bribe_succeeds(&self) -> bool {
-        let mut rng = rand::thread_rng();
-        rng.gen_ratio(1, 2)
+        with_rng(|rng| rng.gen_ratio(1, 2))
     }
 
     fn run_away_succeeds(&self, player_level: i32, enemy_level: i32) -> bool {
-        let mut rng = rand::thread_rng();
-        match player_level {
+        with_rng(|rng| match player_level {
             pl if pl == enemy_level => rng.gen_ratio(1, 3),
             pl if pl > enemy_level => rng.gen_ratio(2, 3),
             _ => rng.gen_ratio(1, 5),
-        }
+        })
     }
 
     fn enemy_level(&self, level: i32) -> i32 {
-        let mut rng = rand::thread_rng();
-        max(1, level + rng.gen_range(-1..2))
+        max(1, level + with_rng(|rng| rng.gen_range(-1..2)))
     }
 
     /// add +/- 20% variance to a the damage
     fn damage(&self, value: i32) -> i32 {
         let value = value as f64;
 
-        let mut rng = rand::thread_rng();
         let min_val = (value * 0.8).floor() as i32;
         let max_val = (value * 1.2).ceil() as i32;
-        max(1, rng.gen_range(min_val..=max_val))
+        max(1, with_rng(|rng| rng.gen_range(min_val..=max_val)))
     }
 
     fn attack_type(
@@ -108,45 +137,47 @@ impl Randomizer for DefaultRandomizer {
         }
     }
 
+    fn confused_self_attack(&self) -> bool {
+        with_rng(|rng| rng.gen_ratio(1, 3))
+    }
+
     fn gold_gained(&self, base: i32) -> i32 {
-        let mut rng = rand::thread_rng();
         let min = (base as f64 * 0.6) as i32;
         let max = (base as f64 * 1.3) as i32;
-        rng.gen_range(min..=max)
+        with_rng(|rng| rng.gen_range(min..=max))
+    }
+
+    fn loot_drops(&self, ratio: u32) -> bool {
+        with_rng(|rng| rng.gen_ratio(1, ratio))
     }
 
     fn stat_increase(&self, increase: i32) -> i32 {
         let min_value = max(1, increase / 2);
         let max_value = 3 * increase / 2;
 
-        let mut rng = rand::thread_rng();
-        rng.gen_range(min_value..=max_value)
+        with_rng(|rng| rng.gen_range(min_value..=max_value))
     }
 
     fn range(&self, max: i32) -> i32 {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(0..max)
+        with_rng(|rng| rng.gen_range(0..max))
     }
 }
 
 fn is_critical() -> bool {
-    let mut rng = rand::thread_rng();
-    rng.gen_ratio(1, 20)
+    with_rng(|rng| rng.gen_ratio(1, 20))
 }
 
 fn is_miss(attacker_speed: i32, receiver_speed: i32) -> bool {
     if receiver_speed > attacker_speed {
         let ratio = receiver_speed / attacker_speed;
         let ratio = max(1, 5 - ratio) as u32;
-        let mut rng = rand::thread_rng();
-        return rng.gen_ratio(1, ratio);
+        return with_rng(|rng| rng.gen_ratio(1, ratio));
     }
     false
 }
 
 fn status_attack(status: StatusEffect, ratio: u32) -> AttackType {
-    let mut rng = rand::thread_rng();
-    if rng.gen_ratio(1, ratio) {
+    if with_rng(|rng| rng.gen_ratio(1, ratio)) {
         AttackType::Effect(status)
     } else {
         AttackType::Regular
@@ -162,6 +193,10 @@ impl Randomizer for TestRandomizer {
         true
     }
 
+    fn should_mimic_appear(&self, _distance: &location::Distance) -> bool {
+        false
+    }
+
     fn bribe_succeeds(&self) -> bool {
         false
     }
@@ -187,10 +222,18 @@ impl Randomizer for TestRandomizer {
         AttackType::Regular
     }
 
+    fn confused_self_attack(&self) -> bool {
+        false
+    }
+
     fn gold_gained(&self, base: i32) -> i32 {
         base
     }
 
+    fn loot_drops(&self, _ratio: u32) -> bool {
+        false
+    }
+
     fn stat_increase(&self, increase: i32) -> i32 {
         increase
     }
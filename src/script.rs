@@ -0,0 +1,101 @@
+use super::{run, Command, Opts};
+use clap::Clap;
+use rpg_core::event::Registry;
+use rpg_core::location::filesystem;
+use rpg_core::{datafile, log, quest, randomizer};
+use rpg_core::{Error, Game};
+
+/// Run the commands of the given file, one per line, against a new game
+/// with seeded randomness, printing each command before its output.
+/// The game is not loaded from nor saved to the data file, and achievements
+/// and hooks are left out, so scripts don't affect the current game.
+/// Only the bundled enemy classes and quests are used, and the user's
+/// home directory is the home, so transcripts don't depend on the user files.
+pub fn run_file(path: &str, seed: u64) -> Result<(), Error> {
+    let script = std::fs::read_to_string(path)
+        .map_err(|err| Error::InvalidInput(format!("Could not read {}: {}", path, err)))?;
+    let commands = parse(&script)?;
+
+    datafile::use_bundled_data();
+    filesystem::set(filesystem::Real::default());
+    randomizer::seed(Some(seed));
+    let mut game = Game::new();
+    game.events = Registry::empty();
+    game.events.subscribe(Box::new(log::Handler));
+    game.events.subscribe(Box::new(quest::Handler));
+
+    for (line, command) in commands {
+        println!("$ rpg {}", line);
+        // relative paths are resolved from the hero's location
+        std::env::set_current_dir(game.location.path_string()).ok();
+        std::env::set_var("PWD", game.location.path_string());
+        match run(&mut game, command) {
+            Ok(()) => {}
+            Err(Error::HeroDied) => game.reset(),
+            Err(err) => println!("{}", err),
+        }
+    }
+    randomizer::seed(None);
+    Ok(())
+}
+
+/// Parse the script lines as rpg arguments, skipping blank lines and
+/// # comments. Fail on the first invalid line, before running any command.
+fn parse(script: &str) -> Result<Vec<(&str, Command)>, Error> {
+    let mut commands = Vec::new();
+    for (number, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let args = std::iter::once("rpg").chain(line.split_whitespace());
+        let opts = Opts::try_parse_from(args)
+            .map_err(|err| Error::InvalidInput(format!("line {}: {}", number + 1, err)))?;
+        match opts.cmd.unwrap_or(Command::Stat) {
+            // moving home changes the user's settings, and simulations
+            // reset the seed of the script
+            Command::Tui
+            | Command::Script { .. }
+            | Command::Simulate { .. }
+            | Command::Home { action: Some(_) } => {
+                return Err(Error::InvalidInput(format!(
+                    "line {}: {} can't run in a script",
+                    number + 1,
+                    line
+                )))
+            }
+            command => commands.push((line, command)),
+        }
+    }
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let script = "# walk around\ncd dev\n\n  ls\nbuy potion\nuse p\nbattle --run\n";
+        let commands = parse(script).unwrap();
+        let lines: Vec<&str> = commands.iter().map(|(line, _)| *line).collect();
+        assert_eq!(
+            vec!["cd dev", "ls", "buy potion", "use p", "battle --run"],
+            lines
+        );
+        assert!(matches!(
+            commands[4].1,
+            Command::Battle {
+                run: true,
+                bribe: false
+            }
+        ));
+
+        assert!(parse("cd\nfly away\n").is_err());
+        assert!(parse("tui\n").is_err());
+        assert!(parse("script other.rpg\n").is_err());
+        assert!(parse("home set /tmp\n").is_err());
+        assert!(parse("simulate --level 3\n").is_err());
+    }
+}
@@ -0,0 +1,258 @@
+/// The shells supported by `rpg init`. The generated code is based on the
+/// snippets of the shell integration guide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `cd` is overridden and how it interacts with battles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CdMode {
+    /// Leave `cd` alone, only define the `rpg` function.
+    None,
+    /// Move one directory at a time, stopping where a battle is won.
+    Stop,
+    /// Always reach the destination, then maybe start a battle there.
+    Arrive,
+}
+
+impl CdMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(CdMode::None),
+            "stop" => Some(CdMode::Stop),
+            "arrive" => Some(CdMode::Arrive),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a comma separated list of command names, returning the first
+/// invalid one as the error. Only plain names are accepted, since they
+/// are pasted into the code the shell evaluates.
+pub fn parse_commands(list: &str) -> Result<Vec<String>, String> {
+    let mut commands = Vec::new();
+    for command in list.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let valid = command
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            return Err(command.to_string());
+        }
+        commands.push(command.to_string());
+    }
+    Ok(commands)
+}
+
+pub struct Options {
+    pub cd: CdMode,
+    /// Commands that can only run after winning a battle.
+    pub battle_on: Vec<String>,
+    /// Look for chests on `ls` without arguments.
+    pub ls: bool,
+    /// Show the hero status in the prompt.
+    pub prompt: bool,
+}
+
+/// Return the integration code for the given shell, ready to be evaluated.
+pub fn init(shell: Shell, options: &Options) -> String {
+    let snippets = match shell {
+        Shell::Bash | Shell::Zsh => posix(shell, options),
+        Shell::Fish => fish(options),
+    };
+    snippets.join("\n")
+}
+
+fn posix(shell: Shell, options: &Options) -> Vec<String> {
+    let mut snippets = vec![String::from(
+        r#"rpg () {
+    rpg-cli "$@"
+    builtin cd "$(rpg-cli pwd)"
+}
+"#,
+    )];
+
+    match options.cd {
+        CdMode::None => {}
+        CdMode::Stop => snippets.push(String::from(
+            r#"cd () {
+    rpg-cli cd "$@"
+    builtin cd "$(rpg-cli pwd)"
+}
+"#,
+        )),
+        CdMode::Arrive => snippets.push(String::from(
+            r#"cd () {
+    builtin cd "$@" || return
+    rpg-cli cd -f .
+    rpg-cli battle
+}
+"#,
+        )),
+    }
+
+    if !options.battle_on.is_empty() {
+        let mut aliases =
+            String::from("rpg_battle () {\n    rpg-cli cd -f . && rpg-cli battle\n}\n");
+        for command in &options.battle_on {
+            aliases.push_str(&format!("alias {0}=\"rpg_battle && {0}\"\n", command));
+        }
+        snippets.push(aliases);
+    }
+
+    if options.ls {
+        snippets.push(String::from(
+            r#"ls () {
+    command ls "$@"
+    if [ $# -eq 0 ] ; then
+        rpg-cli cd -f .
+        rpg-cli ls
+    fi
+}
+"#,
+        ));
+    }
+
+    if options.prompt {
+        let prompt = match shell {
+            Shell::Zsh => "setopt PROMPT_SUBST\nPROMPT='$(rpg-cli prompt) '\"$PROMPT\"\n",
+            _ => "PS1='$(rpg-cli prompt) '\"$PS1\"\n",
+        };
+        snippets.push(String::from(prompt));
+    }
+    snippets
+}
+
+fn fish(options: &Options) -> Vec<String> {
+    let mut snippets = vec![String::from(
+        r#"function rpg
+    rpg-cli $argv
+    builtin cd (rpg-cli pwd)
+end
+"#,
+    )];
+
+    match options.cd {
+        CdMode::None => {}
+        CdMode::Stop => snippets.push(String::from(
+            r#"function cd
+    rpg-cli cd $argv
+    builtin cd (rpg-cli pwd)
+end
+"#,
+        )),
+        CdMode::Arrive => snippets.push(String::from(
+            r#"function cd
+    builtin cd $argv; or return
+    rpg-cli cd -f .
+    rpg-cli battle
+end
+"#,
+        )),
+    }
+
+    if !options.battle_on.is_empty() {
+        let mut functions =
+            String::from("function rpg_battle\n    rpg-cli cd -f .; and rpg-cli battle\nend\n");
+        for command in &options.battle_on {
+            functions.push_str(&format!(
+                "function {0}\n    rpg_battle; and command {0} $argv\nend\n",
+                command
+            ));
+        }
+        snippets.push(functions);
+    }
+
+    if options.ls {
+        snippets.push(String::from(
+            r#"function ls
+    command ls $argv
+    if test (count $argv) -eq 0
+        rpg-cli cd -f .
+        rpg-cli ls
+    end
+end
+"#,
+        ));
+    }
+
+    if options.prompt {
+        snippets.push(String::from(
+            r#"functions -q _rpg_original_prompt; or functions -c fish_prompt _rpg_original_prompt
+function fish_prompt
+    echo -n (rpg-cli prompt)' '
+    _rpg_original_prompt
+end
+"#,
+        ));
+    }
+    snippets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(cd: CdMode) -> Options {
+        Options {
+            cd,
+            battle_on: vec![String::from("rm"), String::from("mv")],
+            ls: false,
+            prompt: true,
+        }
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Ok(vec![String::from("rm"), String::from("git-rm")]),
+            parse_commands("rm, git-rm,")
+        );
+        assert!(parse_commands("").unwrap().is_empty());
+        assert_eq!(
+            Err(String::from("rm;reboot")),
+            parse_commands("mv,rm;reboot")
+        );
+        assert!(parse_commands("$(reboot)").is_err());
+        assert!(parse_commands("rm -rf").is_err());
+        assert!(parse_commands("rm`x`").is_err());
+    }
+
+    #[test]
+    fn test_posix() {
+        let code = init(Shell::Bash, &options(CdMode::None));
+        assert!(code.starts_with("rpg () {"));
+        assert!(!code.contains("cd () {"));
+        assert!(code.contains("alias rm=\"rpg_battle && rm\""));
+        assert!(code.contains("alias mv=\"rpg_battle && mv\""));
+        assert!(!code.contains("ls () {"));
+        assert!(code.contains("PS1="));
+
+        let code = init(Shell::Zsh, &options(CdMode::Arrive));
+        assert!(code.contains("builtin cd \"$@\" || return"));
+        assert!(code.contains("PROMPT_SUBST"));
+    }
+
+    #[test]
+    fn test_fish() {
+        let code = init(Shell::Fish, &options(CdMode::Stop));
+        assert!(code.starts_with("function rpg\n"));
+        assert!(code.contains("rpg-cli cd $argv"));
+        assert!(code.contains("function rm\n    rpg_battle; and command rm $argv\nend"));
+        assert!(code.contains("function fish_prompt"));
+        assert!(!code.contains("alias"));
+    }
+}
@@ -0,0 +1,120 @@
+use rpg_core::event::{self, Event};
+use rpg_core::game::battle::AttackType;
+use rpg_core::{Character, Game};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Collects a text line for each game event, to be displayed in the log
+/// pane instead of printing them to stdout.
+pub struct Journal {
+    lines: Rc<RefCell<Vec<String>>>,
+}
+
+impl Journal {
+    pub fn new(lines: Rc<RefCell<Vec<String>>>) -> Self {
+        Self { lines }
+    }
+}
+
+impl event::Subscriber for Journal {
+    fn handle(&mut self, game: &mut Game, event: &Event) {
+        if let Some(line) = describe(game, event) {
+            self.lines.borrow_mut().push(line);
+        }
+    }
+}
+
+fn describe(game: &Game, event: &Event) -> Option<String> {
+    let hero = character(&game.player);
+    let line = match event {
+        Event::Visit { .. } => return None,
+        Event::EnemyAppears { enemy } => {
+            format!("{} appears at {}", character(enemy), game.location.name())
+        }
+        Event::Bribe { cost } => format!("{} bribed the enemy with {}g", hero, cost),
+        Event::RunAway { success: true } => format!("{} ran away", hero),
+        Event::RunAway { success: false } => format!("{} failed to run away", hero),
+        Event::PlayerAttack {
+            enemy,
+            kind,
+            damage,
+            ..
+        } => attack(&character(enemy), kind, *damage),
+        Event::EnemyAttack { kind, damage, .. } => attack(&hero, kind, *damage),
+        Event::StatusEffectDamage { damage } => format!("{} -{}hp from status", hero, damage),
+        Event::EnemyStatusEffectDamage { enemy, damage } => {
+            format!("{} -{}hp from status", character(enemy), damage)
+        }
+        Event::SkillUsed { skill } => format!("{} used {}", hero, skill),
+        Event::Stunned => format!("{} is stunned", hero),
+        Event::ConfusedAttack { damage } => format!("{} -{}hp hit itself", hero, damage),
+        Event::BattleWon {
+            enemy,
+            xp,
+            levels_up,
+            gold,
+            items,
+            ..
+        } => {
+            let mut line = format!("{} defeated {} +{}xp", hero, character(enemy), xp);
+            if *levels_up > 0 {
+                line.push_str(&format!(" +{}lv", levels_up));
+            }
+            line.push_str(&loot(items, *gold));
+            line
+        }
+        Event::BattleLost => format!("{} died", hero),
+        Event::LevelUp { current } => format!("{} reached level {}", hero, current),
+        Event::Heal {
+            item, recovered, ..
+        } => match item {
+            Some(item) => format!("{} used {} +{}hp", hero, item, recovered),
+            None => format!("{} rested at home +{}hp", hero, recovered),
+        },
+        Event::ItemBought { item, cost } => format!("bought {} for {}g", item, cost),
+        Event::ItemUsed { item } => format!("{} used {}", hero, item),
+        Event::ChestFound { items, gold } => format!("found a chest{}", loot(items, *gold)),
+        Event::TombstoneFound { items, gold } => {
+            format!("found a tombstone{}", loot(items, *gold))
+        }
+        Event::QuestCompleted {
+            description,
+            reward,
+        } => format!("quest completed: {} {}", description, reward),
+        Event::AchievementUnlocked { achievement } => {
+            format!("achievement unlocked: {}", achievement.description())
+        }
+        Event::WarpFound { name } => format!("warp point found: {}", name),
+        Event::Warp { name, cost, .. } => format!("{} warped to {} -{}g", hero, name, cost),
+    };
+    Some(line)
+}
+
+fn character(character: &Character) -> String {
+    format!("{}[{}]", character.name(), character.level)
+}
+
+fn attack(receiver: &str, kind: &AttackType, damage: i32) -> String {
+    match kind {
+        AttackType::Regular => format!("{} -{}hp", receiver, damage),
+        AttackType::Critical => format!("{} -{}hp critical!", receiver, damage),
+        AttackType::Effect(effect) => format!(
+            "{} -{}hp {}",
+            receiver,
+            damage,
+            format!("{:?}", effect).to_lowercase()
+        ),
+        AttackType::Miss => format!("{} dodged!", receiver),
+    }
+}
+
+fn loot(items: &[String], gold: i32) -> String {
+    let mut line = String::new();
+    for item in items {
+        line.push_str(&format!(" +{}", item));
+    }
+    if gold > 0 {
+        line.push_str(&format!(" +{}g", gold));
+    }
+    line
+}
@@ -0,0 +1,277 @@
+use crossterm::cursor::Show;
+use crossterm::event::{self, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use rpg_core::event::Registry;
+use rpg_core::item::shop;
+use rpg_core::{achievement, hook, quest};
+use rpg_core::{Error, Game, Location};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+use tui::backend::CrosstermBackend;
+use tui::widgets::ListState;
+use tui::Terminal;
+
+mod journal;
+mod ui;
+
+/// The tabs below the status panel.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Pane {
+    Browse,
+    Shop,
+    Quests,
+}
+
+impl Pane {
+    pub const ALL: [Pane; 3] = [Pane::Browse, Pane::Shop, Pane::Quests];
+
+    fn next(self) -> Self {
+        match self {
+            Pane::Browse => Pane::Shop,
+            Pane::Shop => Pane::Quests,
+            Pane::Quests => Pane::Browse,
+        }
+    }
+}
+
+/// The interface state that is not part of the game.
+pub struct App {
+    pub pane: Pane,
+    pub selected: ListState,
+    pub log: Rc<RefCell<Vec<String>>>,
+    /// How many lines the log is scrolled up from the latest one.
+    pub scroll: usize,
+    quit: bool,
+}
+
+impl App {
+    fn new(log: Rc<RefCell<Vec<String>>>) -> Self {
+        let mut selected = ListState::default();
+        selected.select(Some(0));
+        Self {
+            pane: Pane::Browse,
+            selected,
+            log,
+            scroll: 0,
+            quit: false,
+        }
+    }
+
+    fn message(&self, line: &str) {
+        self.log.borrow_mut().push(line.to_string());
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected.select(Some(index));
+    }
+
+    fn selected(&self) -> usize {
+        self.selected.selected().unwrap_or(0)
+    }
+}
+
+/// Run the full screen interface until the player quits. The game events
+/// are shown in the log pane instead of stdout, so the default logging
+/// is left out while it runs.
+pub fn run(game: &mut Game) -> io::Result<()> {
+    colored::control::set_override(false);
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let mut events = Registry::empty();
+    events.subscribe(Box::new(quest::Handler));
+    events.subscribe(Box::new(achievement::Handler));
+    events.subscribe(Box::new(hook::Handler::load()));
+    events.subscribe(Box::new(journal::Journal::new(lines.clone())));
+    let events = std::mem::replace(&mut game.events, events);
+
+    let result = run_terminal(game, &mut App::new(lines));
+    game.events = events;
+    result
+}
+
+fn run_terminal(game: &mut Game, app: &mut App) -> io::Result<()> {
+    enable_raw_mode()?;
+    let result = draw_screen(game, app);
+
+    // restore the terminal even if the screen failed
+    let restored =
+        disable_raw_mode().and_then(|_| execute!(io::stdout(), LeaveAlternateScreen, Show));
+    result.and(restored)
+}
+
+fn draw_screen(game: &mut Game, app: &mut App) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    main_loop(&mut terminal, game, app)
+}
+
+fn main_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    game: &mut Game,
+    app: &mut App,
+) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, game, app))?;
+        if let event::Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                handle_key(game, app, key);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_key(game: &mut Game, app: &mut App, key: KeyEvent) {
+    game.quests
+        .refresh_bounties(game.player.level, quest::today());
+    let entries = entries(game, app.pane);
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.quit = true,
+        KeyCode::Tab => {
+            app.pane = app.pane.next();
+            app.select(0);
+        }
+        KeyCode::Up | KeyCode::Char('k') => app.select(app.selected().saturating_sub(1)),
+        KeyCode::Down | KeyCode::Char('j') => {
+            let last = entries.len().saturating_sub(1);
+            app.select(std::cmp::min(app.selected() + 1, last));
+        }
+        KeyCode::PageUp => app.scroll += 5,
+        KeyCode::PageDown => app.scroll = app.scroll.saturating_sub(5),
+        KeyCode::Enter => match app.pane {
+            Pane::Browse => {
+                if let Some(dest) = browse_entries(game).into_iter().nth(app.selected()) {
+                    go_to(game, app, &dest);
+                }
+            }
+            Pane::Shop => buy(game, app),
+            Pane::Quests => {}
+        },
+        KeyCode::Char('h') => go_to(game, app, &Location::home()),
+        KeyCode::Char('l') => {
+            let result = game.inspect();
+            after_action(game, app, result.map_err(Error::from));
+        }
+        KeyCode::Char('p') => use_item(game, app, "potion"),
+        KeyCode::Char('r') => use_item(game, app, "remedy"),
+        KeyCode::Char('e') => use_item(game, app, "escape"),
+        _ => {}
+    }
+}
+
+/// The locations listed in the browse pane: the parent directory
+/// followed by the subdirectories of the current one.
+pub fn browse_entries(game: &Game) -> Vec<Location> {
+    let mut entries = game.location.parent().into_iter().collect::<Vec<_>>();
+    entries.extend(game.location.children());
+    entries
+}
+
+/// The text of each row of the given pane.
+pub fn entries(game: &Game, pane: Pane) -> Vec<String> {
+    match pane {
+        Pane::Browse => {
+            let parent = game.location.parent();
+            browse_entries(game)
+                .iter()
+                .map(|location| {
+                    if Some(location) == parent.as_ref() {
+                        String::from("..")
+                    } else {
+                        format!("{}/", location.name())
+                    }
+                })
+                .collect()
+        }
+        Pane::Shop => {
+            if !game.is_safe() {
+                return vec![String::from("The shop is only open at home and camps.")];
+            }
+            shop::available_items(&game.player)
+                .iter()
+                .map(|(_, item)| format!("{:<12}{}g", item.to_string(), item.cost()))
+                .collect()
+        }
+        Pane::Quests => {
+            let (todo, _) = game.quests.list(game, false);
+            todo.iter()
+                .map(|entry| {
+                    let progress = &entry.progress;
+                    format!(
+                        "{} {}/{} {}",
+                        entry.description, progress.current, progress.target, entry.reward
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+fn go_to(game: &mut Game, app: &mut App, dest: &Location) {
+    let result = game.go_to(dest, false, false);
+    after_action(game, app, result.map_err(Error::from));
+    app.select(0);
+}
+
+fn buy(game: &mut Game, app: &mut App) {
+    if !game.is_safe() {
+        return;
+    }
+    let items = shop::available_items(&game.player);
+    if let Some((name, _)) = items.get(app.selected()) {
+        let result = shop::buy(game, name);
+        after_action(game, app, result.map_err(Error::from));
+    }
+}
+
+fn use_item(game: &mut Game, app: &mut App, name: &str) {
+    let result = game.use_item(name);
+    after_action(game, app, result.map_err(Error::from));
+}
+
+/// Show the error of an action, starting a new game if the hero died.
+fn after_action(game: &mut Game, app: &mut App, result: Result<(), Error>) {
+    match result {
+        Ok(()) => {}
+        Err(Error::HeroDied) => {
+            game.reset();
+            app.message("a new hero starts at home");
+        }
+        Err(err) => app.message(&err.to_string()),
+    }
+    app.scroll = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rpg_core::location::filesystem;
+
+    #[test]
+    fn test_entries() {
+        filesystem::set(filesystem::Memory::new("/home/hero", &["dev/rpg", "music"]));
+        let mut game = Game::new();
+
+        let parent = Location::from("/home").unwrap();
+        let dev = Location::from("~/dev").unwrap();
+        let music = Location::from("~/music").unwrap();
+        assert_eq!(vec![parent, dev.clone(), music], browse_entries(&game));
+        assert_eq!(vec!["..", "dev/", "music/"], entries(&game, Pane::Browse));
+        let shop = entries(&game, Pane::Shop);
+        assert!(shop.iter().any(|row| row.starts_with("potion")));
+        assert!(!entries(&game, Pane::Quests).is_empty());
+
+        game.location = dev;
+        assert_eq!(vec!["..", "rpg/"], entries(&game, Pane::Browse));
+        assert_eq!(
+            vec!["The shop is only open at home and camps."],
+            entries(&game, Pane::Shop)
+        );
+
+        filesystem::set(filesystem::Real::default());
+    }
+}
@@ -0,0 +1,129 @@
+use super::{entries, App, Pane};
+use rpg_core::{log, Game};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::Spans;
+use tui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Tabs};
+use tui::Frame;
+
+const HELP: &str =
+    "q quit  tab pane  ↑↓ select  enter go/buy  h home  l look  p potion  r remedy  e escape";
+
+pub fn draw<B: Backend>(frame: &mut Frame<B>, game: &Game, app: &mut App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(10), Constraint::Length(1)])
+        .split(frame.size());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(rows[0]);
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Min(3)])
+        .split(columns[0]);
+
+    draw_status(frame, game, left[0]);
+    draw_pane(frame, game, app, left[1]);
+    draw_log(frame, app, columns[1]);
+    frame.render_widget(Paragraph::new(HELP), rows[1]);
+}
+
+/// The hero bars, equipment, inventory and gold.
+fn draw_status<B: Backend>(frame: &mut Frame<B>, game: &Game, area: Rect) {
+    let player = &game.player;
+    let title = format!(" {}[{}]@{} ", player.name(), player.level, game.location);
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+
+    let bars = [
+        ("hp", player.current_hp, player.max_hp, Color::Green),
+        ("mp", player.current_mp, player.max_mp, Color::Blue),
+        ("xp", player.xp, player.xp_for_next(), Color::White),
+    ];
+    for (row, (name, current, total, color)) in bars.iter().enumerate() {
+        let ratio = if *total > 0 {
+            (*current as f64 / *total as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(*color))
+            .ratio(ratio)
+            .label(format!("{} {}/{}", name, current, total));
+        frame.render_widget(gauge, rows[row]);
+    }
+
+    let mut status = vec![
+        Spans::from(log::format_equipment(player)),
+        Spans::from(log::format_inventory(game)),
+        Spans::from(format!("{}g", game.gold)),
+    ];
+    let mut effects = log::format_buffs(player);
+    if let Some(effect) = player.status_effect {
+        effects = format!("{:?} {}", effect, effects).to_lowercase();
+    }
+    if !effects.trim().is_empty() {
+        status.push(Spans::from(effects));
+    }
+    frame.render_widget(Paragraph::new(status), rows[3]);
+}
+
+/// The browse, shop and quest tabs with the list of the selected one.
+fn draw_pane<B: Backend>(frame: &mut Frame<B>, game: &Game, app: &mut App, area: Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(area);
+
+    let titles = ["Browse", "Shop", "Quests"]
+        .iter()
+        .map(|title| Spans::from(*title))
+        .collect();
+    let index = Pane::ALL.iter().position(|p| *p == app.pane).unwrap();
+    let tabs = Tabs::new(titles)
+        .select(index)
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    frame.render_widget(tabs, rows[0]);
+
+    let items = entries(game, app.pane)
+        .into_iter()
+        .map(ListItem::new)
+        .collect::<Vec<ListItem>>();
+    if app.selected() >= items.len() {
+        app.select(items.len().saturating_sub(1));
+    }
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, rows[1], &mut app.selected);
+}
+
+/// The latest events, scrolled up with page up and down.
+fn draw_log<B: Backend>(frame: &mut Frame<B>, app: &mut App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Log ");
+    let height = block.inner(area).height as usize;
+    let log = app.log.borrow();
+
+    app.scroll = std::cmp::min(app.scroll, log.len().saturating_sub(height));
+    let end = log.len() - app.scroll;
+    let start = end.saturating_sub(height);
+    let lines = log[start..end]
+        .iter()
+        .map(|line| Spans::from(line.as_str()))
+        .collect::<Vec<Spans>>();
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
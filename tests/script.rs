@@ -0,0 +1,34 @@
+//! Golden transcripts of `rpg script`. Each `.rpg` file in `tests/scripts`
+//! runs in an empty home with a fixed seed, and its output must match
+//! the `.out` file next to it.
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+use std::{env, fs};
+
+#[test]
+fn test_walk() {
+    assert_transcript("walk", 1);
+}
+
+fn assert_transcript(name: &str, seed: u64) {
+    let scripts = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scripts");
+    let home = env::temp_dir().join(format!("rpg-script-{}-{}", name, std::process::id()));
+    fs::create_dir_all(home.join("dev/rpg")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rpg-cli"))
+        .arg("script")
+        .arg(scripts.join(format!("{}.rpg", name)))
+        .args(&["--seed", &seed.to_string()])
+        .env("HOME", &home)
+        .env_remove("RPG_LOGICAL_PATHS")
+        .env_remove("CLICOLOR_FORCE")
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&home).unwrap();
+
+    assert!(output.status.success());
+    let expected = fs::read_to_string(scripts.join(format!("{}.out", name))).unwrap();
+    assert_eq!(expected, String::from_utf8(output.stdout).unwrap());
+}